@@ -0,0 +1,160 @@
+// Baseline benchmarks of the hot paths, to judge the performance impact of changes.
+// Run with `cargo bench`, or `cargo bench --features bulk` to include `privatize_bulk`.
+// The legacy functions taking the deprecated `ObfuscateBelow10Mode` are benchmarked too.
+#![allow(deprecated)]
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use laplace_rs::mechanism::{LaplaceMechanism, Mechanism};
+use laplace_rs::{
+    get_from_cache_or_privatize, privatize, privatize_batch, privatize_batch_per_bin, ObfCache,
+    ObfuscateBelow10Mode,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn bench_privatize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("privatize");
+    let mut rng = StdRng::seed_from_u64(42);
+    for rounding_step in [1, 10] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(rounding_step),
+            &rounding_step,
+            |b, &rounding_step| {
+                b.iter(|| privatize(black_box(1000), 1.0, 0.1, rounding_step, &mut rng).unwrap())
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_mechanism(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mechanism");
+    let mut rng = StdRng::seed_from_u64(42);
+    let values: Vec<f64> = (0..10_000).map(f64::from).collect();
+
+    group.bench_function("reused", |b| {
+        b.iter(|| {
+            let mechanism = LaplaceMechanism::new(1.0, 0.1);
+            for &value in &values {
+                black_box(mechanism.perturb(value, &mut rng).unwrap());
+            }
+        })
+    });
+    group.bench_function("per_call", |b| {
+        b.iter(|| {
+            for &value in &values {
+                let mechanism = LaplaceMechanism::new(1.0, 0.1);
+                black_box(mechanism.perturb(value, &mut rng).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+fn bench_get_from_cache_or_privatize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_from_cache_or_privatize");
+    let mut rng = StdRng::seed_from_u64(42);
+    let obfuscate = |obf_cache: Option<&mut ObfCache>, bin, rng: &mut StdRng| {
+        get_from_cache_or_privatize(
+            black_box(1000),
+            1.0,
+            0.1,
+            bin,
+            obf_cache,
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            10,
+            rng,
+        )
+        .unwrap()
+    };
+
+    group.bench_function("without_cache", |b| b.iter(|| obfuscate(None, 1, &mut rng)));
+
+    let mut obf_cache = ObfCache::new();
+    obfuscate(Some(&mut obf_cache), 1, &mut rng);
+    group.bench_function("cache_hit", |b| {
+        b.iter(|| obfuscate(Some(&mut obf_cache), 1, &mut rng))
+    });
+
+    // every lookup is a miss on a fresh bin, so the cache grows by one entry per iteration
+    let mut obf_cache = ObfCache::new();
+    let mut bin = 0;
+    group.bench_function("cache_miss", |b| {
+        b.iter(|| {
+            bin += 1;
+            obfuscate(Some(&mut obf_cache), bin, &mut rng)
+        })
+    });
+    group.finish();
+}
+
+fn bench_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batch");
+    group.sample_size(10);
+    let mut rng = StdRng::seed_from_u64(42);
+    let len = 1_000_000;
+    let values: Vec<u64> = (0..len as u64).map(|value| value % 10_000).collect();
+    let bins: Vec<usize> = (0..len).collect();
+
+    group.bench_function("privatize_batch", |b| {
+        b.iter_batched_ref(
+            ObfCache::new,
+            |obf_cache| {
+                privatize_batch(
+                    &values,
+                    1.0,
+                    0.1,
+                    &bins,
+                    Some(obf_cache),
+                    true,
+                    ObfuscateBelow10Mode::Obfuscate,
+                    10,
+                    &mut rng,
+                )
+                .unwrap()
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function("privatize_batch_without_cache", |b| {
+        b.iter(|| {
+            privatize_batch(
+                &values,
+                1.0,
+                0.1,
+                &bins,
+                None,
+                true,
+                ObfuscateBelow10Mode::Obfuscate,
+                10,
+                &mut rng,
+            )
+            .unwrap()
+        })
+    });
+
+    let sensitivities = vec![1.0; len];
+    let epsilons = vec![0.1; len];
+    group.bench_function("privatize_batch_per_bin", |b| {
+        b.iter(|| {
+            privatize_batch_per_bin(&values, &sensitivities, &epsilons, 10, &mut rng).unwrap()
+        })
+    });
+
+    #[cfg(feature = "bulk")]
+    group.bench_function("privatize_bulk", |b| {
+        b.iter(|| laplace_rs::privatize_bulk(&values, 1.0, 0.1, 10, &mut rng).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_privatize,
+    bench_mechanism,
+    bench_get_from_cache_or_privatize,
+    bench_batch
+);
+criterion_main!(benches);
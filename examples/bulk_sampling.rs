@@ -0,0 +1,35 @@
+// Compares the throughput of obfuscating a large batch with `privatize_bulk`, which samples
+// the noise in bulk, and with `privatize_batch_per_bin`, which samples it value by value.
+// Run with `cargo run --release --example bulk_sampling --features bulk`.
+
+use std::time::Instant;
+
+use laplace_rs::errors::LaplaceError;
+use laplace_rs::{privatize_batch_per_bin, privatize_bulk};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const LEN: usize = 10_000_000;
+
+fn main() -> Result<(), LaplaceError> {
+    let values = vec![1000; LEN];
+    let sensitivities = vec![1.0; LEN];
+    let epsilons = vec![0.1; LEN];
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let start = Instant::now();
+    privatize_batch_per_bin(&values, &sensitivities, &epsilons, 10, &mut rng)?;
+    let per_sample = start.elapsed();
+    println!("per sample: {per_sample:?}");
+
+    let start = Instant::now();
+    privatize_bulk(&values, 1.0, 0.1, 10, &mut rng)?;
+    let bulk = start.elapsed();
+    println!("bulk: {bulk:?}");
+
+    println!(
+        "speedup: {:.2}x",
+        per_sample.as_secs_f64() / bulk.as_secs_f64()
+    );
+    Ok(())
+}
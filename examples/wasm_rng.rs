@@ -0,0 +1,21 @@
+// Drives the obfuscation without `rand::thread_rng`, which isn't available on WASM. Any
+// random generator implementing `rand::Rng` can be passed, e.g. a `StdRng` seeded from
+// `getrandom`, or the seeded generator owned by an `Obfuscator`. On wasm32-unknown-unknown,
+// enable the `js` feature so that `getrandom` uses the browser's crypto API.
+
+use laplace_rs::errors::LaplaceError;
+use laplace_rs::obfuscator::Obfuscator;
+use laplace_rs::privatize;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn main() -> Result<(), LaplaceError> {
+    let mut rng = StdRng::from_entropy();
+    let obfuscated = privatize(27, 1.0, 0.1, 10, &mut rng)?;
+    println!("obfuscated with StdRng: {obfuscated}");
+
+    let mut obfuscator = Obfuscator::from_entropy();
+    let obfuscated = obfuscator.privatize(27, 1.0, 0.1, 10)?;
+    println!("obfuscated with Obfuscator: {obfuscated}");
+    Ok(())
+}
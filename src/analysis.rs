@@ -0,0 +1,271 @@
+use statrs::distribution::{ContinuousCDF, Laplace};
+
+use crate::errors::LaplaceError;
+use crate::{laplace_scale, validate_privacy_parameters};
+
+/// Computes the variance `2 * b^2` of the noise of the (epsilon, 0) laplacian mechanism, with
+/// scale `b = sensitivity / epsilon`.
+///
+/// # Arguments
+///
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+pub fn laplace_variance(sensitivity: f64, epsilon: f64) -> f64 {
+    let b = sensitivity / epsilon;
+    2.0 * b * b
+}
+
+/// Computes the expected absolute error `b` of the (epsilon, 0) laplacian mechanism, with
+/// scale `b = sensitivity / epsilon`, before rounding.
+///
+/// # Arguments
+///
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+pub fn expected_absolute_error(sensitivity: f64, epsilon: f64) -> f64 {
+    sensitivity / epsilon
+}
+
+/// Computes the epsilon needed for an expected relative error of at most `target_rel_error`
+/// on values around `expected_value`, by inverting `expected_absolute_error`:
+/// `epsilon = sensitivity / (target_rel_error * expected_value)`. Rounding is ignored.
+///
+/// # Arguments
+///
+/// * `expected_value` - The typical magnitude of the clear values, e.g. 1000.
+/// * `sensitivity` - Sensitivity of query.
+/// * `target_rel_error` - The expected relative error, e.g. 0.1 for 10%.
+///
+/// # Returns
+///
+/// The required privacy budget parameter, or an error if the parameters are not positive or
+/// imply an invalid epsilon.
+pub fn epsilon_for_relative_error(
+    expected_value: f64,
+    sensitivity: f64,
+    target_rel_error: f64,
+) -> Result<f64, LaplaceError> {
+    for (name, value) in [
+        ("expected_value", expected_value),
+        ("target_rel_error", target_rel_error),
+    ] {
+        if !(value > 0.0 && value.is_finite()) {
+            return Err(LaplaceError::NonPositiveParameter { name, value });
+        }
+    }
+    let epsilon = sensitivity / (target_rel_error * expected_value);
+    validate_privacy_parameters(sensitivity, epsilon)?;
+    Ok(epsilon)
+}
+
+/// Computes the symmetric interval around an obfuscated value that contains the clear value
+/// with the given confidence, ignoring rounding, using the quantile function of the Laplace
+/// distribution.
+///
+/// # Arguments
+///
+/// * `obfuscated` - The obfuscated value.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * `confidence` - The confidence level, in the open interval (0, 1), e.g. 0.95.
+///
+/// # Returns
+///
+/// The lower and upper end of the interval, or an error if the parameters are invalid.
+pub fn confidence_interval(
+    obfuscated: u64,
+    sensitivity: f64,
+    epsilon: f64,
+    confidence: f64,
+) -> Result<(f64, f64), LaplaceError> {
+    if !(confidence > 0.0 && confidence < 1.0) {
+        return Err(LaplaceError::InvalidConfidence(confidence));
+    }
+    let b = laplace_scale(sensitivity, epsilon)?;
+    let dist = Laplace::new(0.0, b).map_err(LaplaceError::DistributionCreationError)?;
+    let half_width = dist.inverse_cdf((1.0 + confidence) / 2.0);
+    Ok((
+        obfuscated as f64 - half_width,
+        obfuscated as f64 + half_width,
+    ))
+}
+
+/// Computes the probability that obfuscating the value with `privatize` leaves it unchanged,
+/// i.e. that the rounded noisy value equals the rounded clear value, from the Laplace CDF over
+/// the rounding bucket of the rounded clear value. Since negative noisy values are rounded to
+/// 0, the bucket of 0 extends to negative infinity.
+///
+/// # Arguments
+///
+/// * `value` - The clear value.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * `rounding_step` - The granularity of the rounding, must be positive.
+///
+/// # Returns
+///
+/// The probability, or an error if the rounding step is zero.
+pub fn p_unchanged(
+    value: u64,
+    sensitivity: f64,
+    epsilon: f64,
+    rounding_step: usize,
+) -> Result<f64, LaplaceError> {
+    if rounding_step == 0 {
+        return Err(LaplaceError::InvalidArgRoundingStepZero);
+    }
+    let b = sensitivity / epsilon;
+    if b == 0.0 {
+        return Ok(1.0);
+    }
+    let step = rounding_step as f64;
+    let steps = (value as f64 / step).round();
+    // the CDF of the Laplace distribution centered at the clear value
+    let cdf = |x: f64| {
+        let z = (x - value as f64) / b;
+        if z < 0.0 {
+            0.5 * z.exp()
+        } else {
+            1.0 - 0.5 * (-z).exp()
+        }
+    };
+    let upper = cdf((steps + 0.5) * step);
+    if steps == 0.0 {
+        return Ok(upper);
+    }
+    Ok(upper - cdf((steps - 0.5) * step))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{laplace, privatize, round_parametric};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_laplace_variance() {
+        assert_eq!(laplace_variance(1.0, 1.0), 2.0);
+        assert_eq!(laplace_variance(2.0, 0.5), 32.0);
+    }
+
+    #[test]
+    fn test_expected_absolute_error() {
+        assert_eq!(expected_absolute_error(1.0, 1.0), 1.0);
+        assert_eq!(expected_absolute_error(2.0, 0.5), 4.0);
+    }
+
+    #[test]
+    fn test_empirical_moments_match_analytic() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let (sensitivity, epsilon) = (2.0, 0.5);
+        let samples: Vec<f64> = (0..100_000)
+            .map(|_| laplace(0.0, sensitivity / epsilon, &mut rng).unwrap())
+            .collect();
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+        let mean_absolute = samples.iter().map(|x| x.abs()).sum::<f64>() / n;
+
+        let expected_variance = laplace_variance(sensitivity, epsilon);
+        assert!((variance - expected_variance).abs() < 0.05 * expected_variance);
+        let expected_error = expected_absolute_error(sensitivity, epsilon);
+        assert!((mean_absolute - expected_error).abs() < 0.02 * expected_error);
+    }
+    #[test]
+    fn test_epsilon_for_relative_error() {
+        // 10% of 1000 is an expected absolute error of 100
+        assert!((epsilon_for_relative_error(1000.0, 1.0, 0.1).unwrap() - 0.01).abs() < 1e-15);
+        assert!((epsilon_for_relative_error(50.0, 2.0, 0.2).unwrap() - 0.2).abs() < 1e-15);
+        let epsilon = epsilon_for_relative_error(250.0, 3.0, 0.05).unwrap();
+        assert!((expected_absolute_error(3.0, epsilon) / 250.0 - 0.05).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_epsilon_for_relative_error_invalid() {
+        for target_rel_error in [0.0, -0.1, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                epsilon_for_relative_error(1000.0, 1.0, target_rel_error),
+                Err(LaplaceError::NonPositiveParameter {
+                    name: "target_rel_error",
+                    ..
+                })
+            ));
+        }
+        assert!(matches!(
+            epsilon_for_relative_error(0.0, 1.0, 0.1),
+            Err(LaplaceError::NonPositiveParameter {
+                name: "expected_value",
+                ..
+            })
+        ));
+        assert!(matches!(
+            epsilon_for_relative_error(1000.0, -1.0, 0.1),
+            Err(LaplaceError::InvalidEpsilon(_))
+        ));
+        assert!(matches!(
+            epsilon_for_relative_error(1000.0, 0.0, 0.1),
+            Err(LaplaceError::InvalidEpsilon(_))
+        ));
+    }
+
+    #[test]
+    fn test_confidence_interval_95() {
+        // the 97.5% quantile of a Laplace distribution with scale b is b * ln(20)
+        let (lower, upper) = confidence_interval(100, 1.0, 1.0, 0.95).unwrap();
+        assert!((lower - (100.0 - 20f64.ln())).abs() < 1e-9);
+        assert!((upper - (100.0 + 20f64.ln())).abs() < 1e-9);
+
+        let (lower, upper) = confidence_interval(100, 2.0, 0.5, 0.95).unwrap();
+        assert!((upper - lower - 8.0 * 20f64.ln()).abs() < 1e-9);
+        assert!((upper - lower - 23.965858).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_p_unchanged_analytic() {
+        // the noise must be within half a step of the clear value
+        assert!((p_unchanged(10, 1.0, 1.0, 1).unwrap() - (1.0 - (-0.5f64).exp())).abs() < 1e-12);
+        assert!((p_unchanged(10, 2.0, 0.5, 1).unwrap() - (1.0 - (-0.125f64).exp())).abs() < 1e-12);
+        // 0 is also the result of all negative noisy values
+        assert!(
+            (p_unchanged(0, 1.0, 1.0, 1).unwrap() - (1.0 - 0.5 * (-0.5f64).exp())).abs() < 1e-12
+        );
+        // 12 rounds to 10, whose bucket is [7.5, 12.5)
+        let expected = 1.0 - 0.5 * (-0.5f64).exp() - 0.5 * (-4.5f64).exp();
+        assert!((p_unchanged(12, 1.0, 1.0, 5).unwrap() - expected).abs() < 1e-12);
+        assert_eq!(p_unchanged(12, 1.0, f64::INFINITY, 5).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_p_unchanged_rounding_step_zero() {
+        assert!(matches!(
+            p_unchanged(12, 1.0, 1.0, 0),
+            Err(LaplaceError::InvalidArgRoundingStepZero)
+        ));
+    }
+
+    #[test]
+    fn test_p_unchanged_matches_empirical() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let n = 100_000;
+        for (value, rounding_step) in [(27, 1), (27, 10), (3, 5)] {
+            let target = round_parametric(value as f64, rounding_step).unwrap();
+            let unchanged = (0..n)
+                .filter(|_| privatize(value, 1.0, 0.5, rounding_step, &mut rng).unwrap() == target)
+                .count();
+            let expected = p_unchanged(value, 1.0, 0.5, rounding_step).unwrap();
+            assert!((unchanged as f64 / n as f64 - expected).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_confidence_interval_invalid_confidence() {
+        for confidence in [0.0, 1.0, -0.5, f64::NAN] {
+            assert!(matches!(
+                confidence_interval(100, 1.0, 1.0, confidence),
+                Err(LaplaceError::InvalidConfidence(_))
+            ));
+        }
+        assert!(confidence_interval(100, 1.0, 0.0, 0.95).is_err());
+    }
+}
@@ -0,0 +1,202 @@
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use rand::Rng;
+
+use crate::errors::LaplaceError;
+use crate::{exact_f64, privatize, validate_params};
+
+// tolerance for floating-point error accumulated by repeated spending
+const BUDGET_TOLERANCE: f64 = 1e-9;
+
+/// Tracks the privacy budget consumed by a series of queries under basic (sequential)
+/// composition, where the epsilons of all queries add up.
+#[derive(Clone, Debug)]
+pub struct BudgetAccountant {
+    epsilon_budget: f64,
+    spent: f64,
+}
+
+impl BudgetAccountant {
+    /// Creates an accountant with the given total privacy budget, or an error if the budget is
+    /// not a positive finite number.
+    pub fn new(epsilon_budget: f64) -> Result<Self, LaplaceError> {
+        if !(epsilon_budget > 0.0 && epsilon_budget.is_finite()) {
+            return Err(LaplaceError::InvalidEpsilon(epsilon_budget));
+        }
+        Ok(BudgetAccountant {
+            epsilon_budget,
+            spent: 0.0,
+        })
+    }
+
+    /// The total privacy budget.
+    pub fn epsilon_budget(&self) -> f64 {
+        self.epsilon_budget
+    }
+
+    /// The privacy budget spent so far.
+    pub fn spent(&self) -> f64 {
+        self.spent
+    }
+
+    /// The privacy budget left to spend.
+    pub fn remaining(&self) -> f64 {
+        (self.epsilon_budget - self.spent).max(0.0)
+    }
+
+    /// Debits the given epsilon from the budget. Returns an error and leaves the budget
+    /// untouched if the budget would be exceeded or epsilon is invalid.
+    pub fn spend(&mut self, epsilon: f64) -> Result<(), LaplaceError> {
+        if !(epsilon > 0.0 && epsilon.is_finite()) {
+            return Err(LaplaceError::InvalidEpsilon(epsilon));
+        }
+        if self.spent + epsilon > self.epsilon_budget + BUDGET_TOLERANCE {
+            return Err(LaplaceError::BudgetExhausted {
+                requested: epsilon,
+                remaining: self.remaining(),
+            });
+        }
+        self.spent += epsilon;
+        Ok(())
+    }
+
+    /// Debits epsilon from the budget and obfuscates the value with `privatize`. Nothing is
+    /// obfuscated if the budget is exhausted, and nothing is debited if the parameters or the
+    /// value are invalid.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Clear value to permute.
+    /// * `sensitivity` - Sensitivity of query.
+    /// * `epsilon` - Privacy budget parameter, debited from the budget.
+    /// * `rounding_step` - Rounding to the given number is performed.
+    /// * rng - A secure random generator for seeded randomness.
+    ///
+    /// # Returns
+    ///
+    /// The obfuscated value, or an error if the budget is exhausted or the obfuscation failed.
+    pub fn privatize<R: Rng + ?Sized>(
+        &mut self,
+        value: u64,
+        sensitivity: f64,
+        epsilon: f64,
+        rounding_step: usize,
+        rng: &mut R,
+    ) -> Result<u64, LaplaceError> {
+        validate_params(sensitivity, epsilon, rounding_step)?;
+        exact_f64(value)?;
+        self.spend(epsilon)?;
+        privatize(value, sensitivity, epsilon, rounding_step, rng)
+    }
+}
+
+/// Computes the total epsilon of `k` queries with `epsilon_per_query` each under advanced
+/// composition, `sqrt(2k ln(1/delta_prime)) * epsilon + k * epsilon * (e^epsilon - 1)`. The
+/// composition is (total epsilon, `k * delta + delta_prime`)-differentially private for
+/// (epsilon, delta)-differentially private queries, and grows with `sqrt(k)` instead of `k`.
+///
+/// # Arguments
+///
+/// * `epsilon_per_query` - Privacy budget parameter of each query.
+/// * `k` - Number of queries.
+/// * `delta_prime` - Additional probability of the privacy guarantee failing, in (0, 1).
+pub fn advanced_composition(epsilon_per_query: f64, k: usize, delta_prime: f64) -> f64 {
+    let k = k as f64;
+    (2.0 * k * (1.0 / delta_prime).ln()).sqrt() * epsilon_per_query
+        + k * epsilon_per_query * (epsilon_per_query.exp() - 1.0)
+}
+
+/// Computes the total epsilon of queries on disjoint subsets of the data under parallel
+/// composition, the largest epsilon of the queries, or 0 if there are none.
+pub fn parallel_composition(epsilons: &[f64]) -> f64 {
+    epsilons.iter().copied().fold(0.0, f64::max)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_spend_within_budget() {
+        let mut accountant = BudgetAccountant::new(1.0).unwrap();
+        accountant.spend(0.25).unwrap();
+        accountant.spend(0.5).unwrap();
+        assert!((accountant.spent() - 0.75).abs() < 1e-12);
+        assert!((accountant.remaining() - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_spend_entire_budget() {
+        let mut accountant = BudgetAccountant::new(1.0).unwrap();
+        for _ in 0..10 {
+            accountant.spend(0.1).unwrap();
+        }
+        assert!(accountant.remaining() < 1e-9);
+    }
+
+    #[test]
+    fn test_spend_beyond_budget() {
+        let mut accountant = BudgetAccountant::new(1.0).unwrap();
+        accountant.spend(0.75).unwrap();
+        let result = accountant.spend(0.5);
+        assert!(matches!(
+            result,
+            Err(LaplaceError::BudgetExhausted { requested, .. }) if requested == 0.5
+        ));
+        assert!((accountant.remaining() - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_invalid_epsilon() {
+        assert!(BudgetAccountant::new(0.0).is_err());
+        let mut accountant = BudgetAccountant::new(1.0).unwrap();
+        assert!(matches!(
+            accountant.spend(-0.5),
+            Err(LaplaceError::InvalidEpsilon(_))
+        ));
+    }
+
+    #[test]
+    fn test_privatize_debits_budget() {
+        let mut rng = rand::thread_rng();
+        let mut accountant = BudgetAccountant::new(1.0).unwrap();
+        assert!(accountant.privatize(27, 1.0, 0.5, 10, &mut rng).is_ok());
+        assert!(accountant.privatize(27, 1.0, 0.5, 10, &mut rng).is_ok());
+        assert!(accountant.privatize(27, 1.0, 0.5, 10, &mut rng).is_err());
+        assert!((accountant.spent() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_privatize_invalid_params_keep_budget() {
+        let mut rng = rand::thread_rng();
+        let mut accountant = BudgetAccountant::new(1.0).unwrap();
+        assert!(matches!(
+            accountant.privatize(27, 1.0, 0.5, 0, &mut rng),
+            Err(LaplaceError::InvalidArgRoundingStepZero)
+        ));
+        assert!(matches!(
+            accountant.privatize(27, -1.0, 0.5, 10, &mut rng),
+            Err(LaplaceError::InvalidSensitivity(_))
+        ));
+        assert!(matches!(
+            accountant.privatize(u64::MAX, 1.0, 0.5, 10, &mut rng),
+            Err(LaplaceError::PrecisionLoss(_))
+        ));
+        assert_eq!(accountant.remaining(), 1.0);
+    }
+
+    #[test]
+    fn test_advanced_composition() {
+        // 100 queries with epsilon 0.1: 5.85 instead of 10 under basic composition
+        assert!((advanced_composition(0.1, 100, 1e-5) - 5.850235).abs() < 1e-6);
+        // 10000 queries with epsilon 0.01: 6.26 instead of 100
+        assert!((advanced_composition(0.01, 10_000, 1e-6) - 6.261538).abs() < 1e-6);
+        assert_eq!(advanced_composition(0.1, 0, 1e-5), 0.0);
+    }
+
+    #[test]
+    fn test_parallel_composition() {
+        assert_eq!(parallel_composition(&[0.1, 0.5, 0.3]), 0.5);
+        assert_eq!(parallel_composition(&[]), 0.0);
+    }
+}
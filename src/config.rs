@@ -0,0 +1,807 @@
+use rand::Rng;
+
+use crate::errors::LaplaceError;
+use crate::mechanism::{LaplaceMechanism, Mechanism};
+use crate::sync_cache::SyncObfCache;
+use crate::{
+    exact_f64, laplace_scale, privatize_with_mechanism, round_parametric, round_truncated,
+    steps_to_u64, Bin, CacheKey, ObfCache, RoundingOrder, SmallCountPolicy, SuppressionMode,
+};
+
+/// The parameters of an obfuscation, as an alternative to passing them positionally to
+/// `get_from_cache_or_privatize`.
+///
+/// ```
+/// use laplace_rs::config::DiffPrivConfig;
+/// use laplace_rs::{ObfCache, SmallCountPolicy, SuppressionMode};
+///
+/// let config = DiffPrivConfig::new()
+///     .sensitivity(1.0)
+///     .epsilon(0.1)
+///     .rounding_step(10)
+///     .obfuscate_zero(false)
+///     .small_count_policy(SmallCountPolicy {
+///         threshold: 10,
+///         mode: SuppressionMode::Clamp(10),
+///     });
+///
+/// let mut obf_cache = ObfCache::new();
+/// let mut rng = rand::thread_rng();
+/// let obfuscated = config.privatize(15, 1, Some(&mut obf_cache), &mut rng).unwrap();
+/// assert_eq!(obfuscated % 10, 0);
+/// ```
+///
+/// With the `serde` feature, the config can be loaded from e.g. JSON, with missing fields set
+/// to their defaults.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DiffPrivConfig {
+    #[cfg_attr(feature = "serde", serde(alias = "delta"))]
+    sensitivity: f64,
+    epsilon: f64,
+    rounding_step: usize,
+    rounding: bool,
+    rounding_order: RoundingOrder,
+    obfuscate_zero: bool,
+    small_count_policy: SmallCountPolicy,
+    domain_max: Option<u64>,
+    input_bounds: Option<(u64, u64)>,
+    min_scale: Option<f64>,
+}
+
+impl Default for DiffPrivConfig {
+    /// Sensitivity 1, epsilon 1, rounding to a step of 1, obfuscating all values, unbounded
+    /// domain and input, without a minimum scale.
+    fn default() -> Self {
+        DiffPrivConfig {
+            sensitivity: 1.0,
+            epsilon: 1.0,
+            rounding_step: 1,
+            rounding: true,
+            rounding_order: RoundingOrder::AfterNoise,
+            obfuscate_zero: true,
+            small_count_policy: SmallCountPolicy {
+                threshold: 10,
+                mode: SuppressionMode::Obfuscate,
+            },
+            domain_max: None,
+            input_bounds: None,
+            min_scale: None,
+        }
+    }
+}
+
+impl DiffPrivConfig {
+    /// Creates a config with the default parameters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the sensitivity.
+    pub fn sensitivity(mut self, sensitivity: f64) -> Self {
+        self.sensitivity = sensitivity;
+        self
+    }
+
+    /// Sets the sensitivity. Named after the positional parameter of
+    /// `get_from_cache_or_privatize`, although delta usually denotes the failure probability
+    /// of (epsilon, delta) privacy.
+    #[deprecated(note = "use `sensitivity` instead")]
+    pub fn delta(self, delta: f64) -> Self {
+        self.sensitivity(delta)
+    }
+
+    /// Sets the privacy budget parameter.
+    pub fn epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    /// Sets the granularity of the rounding.
+    pub fn rounding_step(mut self, rounding_step: usize) -> Self {
+        self.rounding_step = rounding_step;
+        self
+    }
+
+    /// Sets whether the obfuscated values are rounded to the rounding step. If false, the
+    /// rounding step is ignored and the noised values are only truncated to integers, see
+    /// `privatize_unrounded`.
+    pub fn rounding(mut self, rounding: bool) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// Sets whether the clear values are also rounded to the rounding step before adding the
+    /// noise, e.g. for input perturbation. Defaults to rounding only after adding the noise.
+    pub fn rounding_order(mut self, rounding_order: RoundingOrder) -> Self {
+        self.rounding_order = rounding_order;
+        self
+    }
+
+    /// Sets whether zero counts should be obfuscated.
+    pub fn obfuscate_zero(mut self, obfuscate_zero: bool) -> Self {
+        self.obfuscate_zero = obfuscate_zero;
+        self
+    }
+
+    /// Sets how values below 10 are obfuscated, see `SmallCountPolicy` for other thresholds.
+    #[deprecated(note = "use `small_count_policy` instead")]
+    #[allow(deprecated)]
+    pub fn below_10_mode(mut self, below_10_mode: crate::ObfuscateBelow10Mode) -> Self {
+        self.small_count_policy = below_10_mode.into();
+        self
+    }
+
+    /// Sets how values below the threshold of the policy are obfuscated.
+    pub fn small_count_policy(mut self, small_count_policy: SmallCountPolicy) -> Self {
+        self.small_count_policy = small_count_policy;
+        self
+    }
+
+    /// Sets the largest value of the domain, see `privatize_truncated`. If None, the
+    /// obfuscated values are unbounded.
+    pub fn domain_max(mut self, domain_max: Option<u64>) -> Self {
+        self.domain_max = domain_max;
+        self
+    }
+
+    /// Sets the bounds the clear value is clamped to before obfuscating, so that values
+    /// outside the bounds the sensitivity was derived from can't weaken the privacy guarantee.
+    /// If None, the clear values are not clamped.
+    pub fn input_bounds(mut self, input_bounds: Option<(u64, u64)>) -> Self {
+        self.input_bounds = input_bounds;
+        self
+    }
+
+    /// Sets the minimum scale `b` of the noise, e.g. as mandated by a policy to guard against
+    /// a misconfigured sensitivity or epsilon adding next to no noise. If the scale
+    /// `sensitivity / epsilon` is smaller, the values are obfuscated with the epsilon
+    /// `sensitivity / min_scale` instead, rounded down so that the scale is at least
+    /// `min_scale`, also with an infinite epsilon. A sensitivity of 0 can't be raised this way
+    /// and keeps its epsilon. If None, the scale isn't raised.
+    pub fn min_scale(mut self, min_scale: Option<f64>) -> Self {
+        self.min_scale = min_scale;
+        self
+    }
+
+    /// The scale `b` of the noise added to the values, `sensitivity / epsilon` raised to at
+    /// least the minimum scale, or an error if the parameters are invalid.
+    pub fn effective_scale(&self) -> Result<f64, LaplaceError> {
+        self.validate()?;
+        laplace_scale(self.sensitivity, self.effective_epsilon())
+    }
+
+    /// Obfuscates the given value with the parameters of this config, see
+    /// `get_from_cache_or_privatize`.
+    ///
+    /// # Arguments
+    ///
+    /// * value - The input value to be obfuscated.
+    /// * bin - The bin that the value belongs to.
+    /// * obf_cache_option - An option that represents the obfuscation cache.
+    /// * rng - A secure random generator for seeded randomness.
+    ///
+    /// # Returns
+    ///
+    /// The obfuscated value, rounded to the nearest multiple of the rounding_step, or an error
+    /// if the obfuscation failed.
+    pub fn privatize<R: Rng + ?Sized>(
+        &self,
+        value: u64,
+        bin: Bin,
+        obf_cache_option: Option<&mut ObfCache>,
+        rng: &mut R,
+    ) -> Result<u64, LaplaceError> {
+        self.privatize_with(value, bin, obf_cache_option, &self.mechanism(), rng)
+    }
+
+    // obfuscates the values like `privatize`, with one mechanism for the whole batch
+    pub(crate) fn privatize_batch<R: Rng + ?Sized>(
+        &self,
+        values: &[u64],
+        bins: &[Bin],
+        mut obf_cache_option: Option<&mut ObfCache>,
+        rng: &mut R,
+    ) -> Result<Vec<u64>, LaplaceError> {
+        let mechanism = self.mechanism();
+        values
+            .iter()
+            .zip(bins)
+            .map(|(&value, &bin)| {
+                self.privatize_with(value, bin, obf_cache_option.as_deref_mut(), &mechanism, rng)
+            })
+            .collect()
+    }
+
+    fn privatize_with<R: Rng + ?Sized>(
+        &self,
+        value: u64,
+        bin: Bin,
+        obf_cache_option: Option<&mut ObfCache>,
+        mechanism: &LaplaceMechanism,
+        rng: &mut R,
+    ) -> Result<u64, LaplaceError> {
+        self.validate()?;
+        let value = self.clamp_input(value)?;
+
+        if let Some(suppressed) = self.suppress(value) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(bin, "value suppressed");
+            return Ok(suppressed);
+        }
+
+        let obfuscated: u64 = match obf_cache_option {
+            None => {
+                let obfuscated_value = self.obfuscate(value, mechanism, rng)?;
+                #[cfg(feature = "tracing")]
+                self.trace_obfuscation(bin, "none", obfuscated_value);
+                obfuscated_value
+            }
+            Some(obf_cache) => {
+                obf_cache.check_bin(bin)?;
+                let key = self.cache_key(value, bin);
+
+                let obfuscated: u64 = match obf_cache.get(&key) {
+                    Some(obfuscated_value) => {
+                        #[cfg(feature = "tracing")]
+                        self.trace_obfuscation(bin, "hit", obfuscated_value);
+                        obfuscated_value
+                    }
+                    None => {
+                        let obfuscated_value = self.obfuscate(value, mechanism, rng)?;
+                        #[cfg(feature = "tracing")]
+                        self.trace_obfuscation(bin, "miss", obfuscated_value);
+
+                        obf_cache.insert(key, obfuscated_value);
+                        obfuscated_value
+                    }
+                };
+                obfuscated
+            }
+        };
+        Ok(obfuscated)
+    }
+
+    /// Obfuscates the given value with the parameters of this config, like `privatize`, but
+    /// using a cache that can be shared between threads.
+    ///
+    /// # Arguments
+    ///
+    /// * value - The input value to be obfuscated.
+    /// * bin - The bin that the value belongs to.
+    /// * obf_cache - The shared obfuscation cache.
+    /// * rng - A secure random generator for seeded randomness.
+    ///
+    /// # Returns
+    ///
+    /// The obfuscated value, rounded to the nearest multiple of the rounding_step, or an error
+    /// if the obfuscation failed.
+    pub fn privatize_sync<R: Rng + ?Sized>(
+        &self,
+        value: u64,
+        bin: Bin,
+        obf_cache: &SyncObfCache,
+        rng: &mut R,
+    ) -> Result<u64, LaplaceError> {
+        self.validate()?;
+        let value = self.clamp_input(value)?;
+        if let Some(suppressed) = self.suppress(value) {
+            return Ok(suppressed);
+        }
+        obf_cache.get_or_try_insert_with(self.cache_key(value, bin), || {
+            self.obfuscate(value, &self.mechanism(), rng)
+        })
+    }
+
+    // logs the parameters of the applied noise and the number of digits of the obfuscated value, but neither the
+    // clear nor the obfuscated value
+    #[cfg(feature = "tracing")]
+    fn trace_obfuscation(&self, bin: Bin, cache: &'static str, obfuscated_value: u64) {
+        let magnitude = obfuscated_value.checked_ilog10().map_or(0, |log| log + 1);
+        tracing::debug!(
+            sensitivity = self.sensitivity,
+            epsilon = self.effective_epsilon(),
+            bin,
+            cache,
+            magnitude,
+            "obfuscated value"
+        );
+    }
+
+    fn validate(&self) -> Result<(), LaplaceError> {
+        if self.domain_max == Some(0) {
+            return Err(LaplaceError::InvalidDomain);
+        }
+        match self.min_scale {
+            Some(min_scale) if !(min_scale > 0.0 && min_scale.is_finite()) => {
+                Err(LaplaceError::NonPositiveParameter {
+                    name: "min_scale",
+                    value: min_scale,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // the epsilon giving the scale raised to the minimum scale, unless the sensitivity is 0,
+    // which would give an epsilon of 0
+    fn effective_epsilon(&self) -> f64 {
+        match self.min_scale {
+            Some(min_scale)
+                if self.sensitivity > 0.0 && self.sensitivity / self.epsilon < min_scale =>
+            {
+                // the scale recomputed from the quotient may be an ulp below the minimum
+                let mut epsilon = self.sensitivity / min_scale;
+                while self.sensitivity / epsilon < min_scale {
+                    epsilon = epsilon.next_down();
+                }
+                epsilon
+            }
+            _ => self.epsilon,
+        }
+    }
+
+    // the clear value clamped to the input bounds
+    fn clamp_input(&self, value: u64) -> Result<u64, LaplaceError> {
+        match self.input_bounds {
+            None => Ok(value),
+            Some((lower, upper)) if lower > upper => {
+                Err(LaplaceError::InvalidInputBounds(lower, upper))
+            }
+            Some((lower, upper)) => Ok(value.clamp(lower, upper)),
+        }
+    }
+
+    // the value returned instead of obfuscating, if suppression applies to the value
+    fn suppress(&self, value: u64) -> Option<u64> {
+        if !self.obfuscate_zero && value == 0 {
+            return Some(0);
+        }
+
+        self.small_count_policy.suppress(value)
+    }
+
+    // the mechanism adding noise with the effective scale
+    fn mechanism(&self) -> LaplaceMechanism {
+        LaplaceMechanism::new(self.sensitivity, self.effective_epsilon())
+    }
+
+    fn cache_key(&self, value: u64, bin: Bin) -> CacheKey {
+        (
+            self.sensitivity.to_bits(),
+            self.effective_epsilon().to_bits(),
+            value,
+            bin,
+        )
+    }
+
+    fn obfuscate<R: Rng + ?Sized>(
+        &self,
+        value: u64,
+        mechanism: &LaplaceMechanism,
+        rng: &mut R,
+    ) -> Result<u64, LaplaceError> {
+        let value = match self.rounding_order {
+            RoundingOrder::BeforeNoise => round_parametric(value as f64, self.rounding_step)?,
+            RoundingOrder::AfterNoise => value,
+        };
+        // small counts are obfuscated like all other values, since choosing the mechanism,
+        // and with it the range of the output, by the clear value would reveal on which side
+        // of the threshold the value is
+        if !self.rounding {
+            let obfuscated_value =
+                steps_to_u64(mechanism.perturb(exact_f64(value)?, rng)?.trunc(), 1);
+            return Ok(self.domain_max.map_or(obfuscated_value, |domain_max| {
+                obfuscated_value.min(domain_max)
+            }));
+        }
+        match self.domain_max {
+            None => privatize_with_mechanism(value, mechanism, self.rounding_step, rng),
+            Some(domain_max) => round_truncated(
+                mechanism.perturb(exact_f64(value)?, rng)?,
+                domain_max,
+                self.rounding_step,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod test {
+    use super::*;
+    use crate::{
+        get_from_cache_or_privatize, privatize, privatize_unrounded, privatize_with_injected_noise,
+        ObfuscateBelow10Mode,
+    };
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_config_matches_positional_call() {
+        let config = DiffPrivConfig::new()
+            .sensitivity(2.0)
+            .epsilon(0.5)
+            .rounding_step(5)
+            .obfuscate_zero(true)
+            .below_10_mode(ObfuscateBelow10Mode::Obfuscate);
+
+        let mut rng_config = StdRng::seed_from_u64(3);
+        let mut rng_positional = StdRng::seed_from_u64(3);
+        let mut cache_config = ObfCache::new();
+        let mut cache_positional = ObfCache::new();
+        for value in [0, 5, 27, 27, 1000] {
+            let from_config = config
+                .privatize(value, 1, Some(&mut cache_config), &mut rng_config)
+                .unwrap();
+            let from_positional = get_from_cache_or_privatize(
+                value,
+                2.0,
+                0.5,
+                1,
+                Some(&mut cache_positional),
+                true,
+                ObfuscateBelow10Mode::Obfuscate,
+                5,
+                &mut rng_positional,
+            )
+            .unwrap();
+            assert_eq!(from_config, from_positional);
+        }
+    }
+
+    #[test]
+    fn test_config_suppression() {
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::new();
+        let config = DiffPrivConfig::new()
+            .obfuscate_zero(false)
+            .below_10_mode(ObfuscateBelow10Mode::Ten);
+        assert_eq!(
+            config
+                .privatize(0, 1, Some(&mut obf_cache), &mut rng)
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            config
+                .privatize(5, 1, Some(&mut obf_cache), &mut rng)
+                .unwrap(),
+            10
+        );
+    }
+    #[test]
+    fn test_config_domain_max() {
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::new();
+        let config = DiffPrivConfig::new()
+            .sensitivity(10.0)
+            .epsilon(0.5)
+            .domain_max(Some(30));
+        for value in 0..100 {
+            let obfuscated = config
+                .privatize(value, 1, Some(&mut obf_cache), &mut rng)
+                .unwrap();
+            assert!(obfuscated <= 30);
+        }
+    }
+
+    #[test]
+    fn test_config_without_rounding() {
+        let config = DiffPrivConfig::new().epsilon(0.5).rounding_step(10);
+        let unrounded = config.clone().rounding(false);
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut rng_unrounded = StdRng::seed_from_u64(3);
+        let mut rng_expected = StdRng::seed_from_u64(3);
+        let mut unaligned = 0;
+        for value in 100..200 {
+            let rounded = config.privatize(value, 1, None, &mut rng).unwrap();
+            let obfuscated = unrounded
+                .privatize(value, 1, None, &mut rng_unrounded)
+                .unwrap();
+            assert_eq!(rounded % 10, 0);
+            assert_eq!(
+                obfuscated,
+                privatize_unrounded(value, 1.0, 0.5, &mut rng_expected).unwrap()
+            );
+            if !obfuscated.is_multiple_of(10) {
+                unaligned += 1;
+            }
+        }
+        assert!(unaligned > 50);
+
+        let bounded = unrounded.domain_max(Some(105));
+        for value in 0..200 {
+            let obfuscated = bounded.privatize(value, 1, None, &mut rng).unwrap();
+            assert!(obfuscated <= 105);
+        }
+    }
+
+    #[test]
+    fn test_config_rounding_order() {
+        let after = DiffPrivConfig::new().epsilon(0.5).rounding_step(10);
+        let before = after.clone().rounding_order(RoundingOrder::BeforeNoise);
+        let mut rng_after = StdRng::seed_from_u64(3);
+        let mut rng_before = StdRng::seed_from_u64(3);
+        let mut rng_noise = StdRng::seed_from_u64(3);
+        let mechanism = LaplaceMechanism::new(1.0, 0.5);
+        let mut differing = 0;
+        for value in 100..200 {
+            let noise = mechanism.perturb(0.0, &mut rng_noise).unwrap();
+            let obfuscated_after = after.privatize(value, 1, None, &mut rng_after).unwrap();
+            let obfuscated_before = before.privatize(value, 1, None, &mut rng_before).unwrap();
+            assert_eq!(
+                obfuscated_after,
+                privatize_with_injected_noise(value, noise, 10).unwrap()
+            );
+            let rounded = round_parametric(value as f64, 10).unwrap();
+            assert_eq!(
+                obfuscated_before,
+                privatize_with_injected_noise(rounded, noise, 10).unwrap()
+            );
+            if obfuscated_after != obfuscated_before {
+                differing += 1;
+            }
+        }
+        assert!(differing > 0);
+
+        // with a noise of 3, 24 rounds to 30, but to 20 if coarsened to 20 before the noise
+        assert_eq!(privatize_with_injected_noise(24, 3.0, 10).unwrap(), 30);
+        assert_eq!(privatize_with_injected_noise(20, 3.0, 10).unwrap(), 20);
+    }
+
+    #[test]
+    fn test_config_invalid_domain() {
+        let mut rng = rand::thread_rng();
+        let config = DiffPrivConfig::new().domain_max(Some(0));
+        let result = config.privatize(10, 1, None, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::InvalidDomain)));
+    }
+
+    #[test]
+    fn test_config_min_scale() {
+        let config = DiffPrivConfig::new().sensitivity(2.0).min_scale(Some(4.0));
+        for epsilon in [0.1, 0.5, 1.0, 10.0, 1e6, f64::INFINITY] {
+            let scale = config.clone().epsilon(epsilon).effective_scale().unwrap();
+            assert!(scale >= 4.0);
+            assert_eq!(scale, (2.0 / epsilon).max(4.0));
+        }
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut rng_floor = StdRng::seed_from_u64(42);
+        let floored = config.clone().epsilon(1e6);
+        let unfloored = DiffPrivConfig::new().sensitivity(2.0).epsilon(0.5);
+        for value in [0, 27, 1000] {
+            assert_eq!(
+                floored.privatize(value, 1, None, &mut rng_floor).unwrap(),
+                unfloored.privatize(value, 1, None, &mut rng).unwrap()
+            );
+        }
+
+        // a sensitivity of 0 keeps its epsilon instead of getting an epsilon of 0
+        let zero_sensitivity = DiffPrivConfig::new()
+            .sensitivity(0.0)
+            .epsilon(f64::INFINITY)
+            .min_scale(Some(4.0));
+        for value in [0, 27, 1000] {
+            assert_eq!(
+                zero_sensitivity
+                    .privatize(value, 1, None, &mut rng)
+                    .unwrap(),
+                value
+            );
+        }
+        let result = zero_sensitivity
+            .epsilon(0.5)
+            .privatize(27, 1, None, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::NonPositiveScale { .. })));
+
+        // the scale reaches the minimum exactly, also where `sensitivity / min_scale` rounds up
+        for (sensitivity, min_scale) in [(5.0, 4.9), (10.0, 4.9), (0.7, 10.3), (2.0, 4.0)] {
+            let floored = DiffPrivConfig::new()
+                .sensitivity(sensitivity)
+                .epsilon(1e6)
+                .min_scale(Some(min_scale));
+            assert!(floored.effective_scale().unwrap() >= min_scale);
+            assert!(floored.mechanism().scale().unwrap() >= min_scale);
+        }
+
+        let result = config.min_scale(Some(0.0)).effective_scale();
+        assert!(matches!(
+            result,
+            Err(LaplaceError::NonPositiveParameter {
+                name: "min_scale",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_config_small_counts_same_support() {
+        let config = DiffPrivConfig::new()
+            .epsilon(0.1)
+            .small_count_policy(SmallCountPolicy {
+                threshold: 10,
+                mode: SuppressionMode::Obfuscate,
+            });
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut rng_privatize = StdRng::seed_from_u64(42);
+        let (mut below_max, mut above_max) = (0, 0);
+        let (mut below_min, mut above_min) = (u64::MAX, u64::MAX);
+        for _ in 0..1000 {
+            let below = config.privatize(9, 1, None, &mut rng).unwrap();
+            assert_eq!(
+                below,
+                privatize(9, 1.0, 0.1, 1, &mut rng_privatize).unwrap()
+            );
+            let above = config.privatize(10, 1, None, &mut rng).unwrap();
+            assert_eq!(
+                above,
+                privatize(10, 1.0, 0.1, 1, &mut rng_privatize).unwrap()
+            );
+            below_max = below_max.max(below);
+            above_max = above_max.max(above);
+            below_min = below_min.min(below);
+            above_min = above_min.min(above);
+        }
+        // both sides of the threshold reach beyond twice the threshold and down to 0
+        assert!(below_max > 20 && above_max > 20);
+        assert_eq!((below_min, above_min), (0, 0));
+    }
+
+    #[test]
+    fn test_config_small_count_policy() {
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::new();
+        let policy = |threshold, mode| SmallCountPolicy { threshold, mode };
+
+        let config = DiffPrivConfig::new().small_count_policy(policy(5, SuppressionMode::Zero));
+        assert_eq!(
+            config
+                .privatize(4, 1, Some(&mut obf_cache), &mut rng)
+                .unwrap(),
+            0
+        );
+
+        let config =
+            DiffPrivConfig::new().small_count_policy(policy(20, SuppressionMode::Clamp(20)));
+        assert_eq!(
+            config
+                .privatize(0, 1, Some(&mut obf_cache), &mut rng)
+                .unwrap(),
+            20
+        );
+        assert_eq!(
+            config
+                .privatize(15, 1, Some(&mut obf_cache), &mut rng)
+                .unwrap(),
+            20
+        );
+        assert!(obf_cache.is_empty());
+
+        let config = DiffPrivConfig::new()
+            .epsilon(0.1)
+            .small_count_policy(policy(20, SuppressionMode::Obfuscate));
+        config
+            .privatize(15, 1, Some(&mut obf_cache), &mut rng)
+            .unwrap();
+        assert_eq!(obf_cache.len(), 1);
+    }
+
+    #[test]
+    fn test_config_input_bounds() {
+        let config = DiffPrivConfig::new().epsilon(0.5);
+        let bounded = config.clone().input_bounds(Some((0, 100)));
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut rng_bounded = StdRng::seed_from_u64(3);
+        for (value, clamped) in [(1000, 100), (100, 100), (27, 27)] {
+            assert_eq!(
+                bounded.privatize(value, 1, None, &mut rng_bounded).unwrap(),
+                config.privatize(clamped, 1, None, &mut rng).unwrap()
+            );
+        }
+
+        let mut obf_cache = ObfCache::new();
+        let first = bounded
+            .privatize(1000, 1, Some(&mut obf_cache), &mut rng)
+            .unwrap();
+        let second = bounded
+            .privatize(100, 1, Some(&mut obf_cache), &mut rng)
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(obf_cache.len(), 1);
+    }
+
+    #[test]
+    fn test_config_invalid_input_bounds() {
+        let mut rng = rand::thread_rng();
+        let config = DiffPrivConfig::new().input_bounds(Some((10, 5)));
+        let result = config.privatize(7, 1, None, &mut rng);
+        assert!(matches!(
+            result,
+            Err(LaplaceError::InvalidInputBounds(10, 5))
+        ));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_config_traces_cache_miss() {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        // records the epsilon and cache fields of each event
+        #[derive(Clone, Default)]
+        struct CacheEvents(Arc<Mutex<Vec<String>>>);
+
+        impl Visit for CacheEvents {
+            fn record_f64(&mut self, field: &Field, value: f64) {
+                if field.name() == "epsilon" {
+                    self.0.lock().unwrap().push(format!("epsilon={value}"));
+                }
+            }
+
+            fn record_str(&mut self, field: &Field, value: &str) {
+                if field.name() == "cache" {
+                    self.0.lock().unwrap().push(value.to_string());
+                }
+            }
+
+            fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+        }
+
+        impl Subscriber for CacheEvents {
+            fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _span: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+            fn record(&self, _span: &Id, _values: &Record<'_>) {}
+            fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                event.record(&mut self.clone());
+            }
+            fn enter(&self, _span: &Id) {}
+            fn exit(&self, _span: &Id) {}
+        }
+
+        let events = CacheEvents::default();
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::new();
+        tracing::subscriber::with_default(events.clone(), || {
+            for _ in 0..2 {
+                get_from_cache_or_privatize(
+                    27,
+                    1.0,
+                    1.0,
+                    1,
+                    Some(&mut obf_cache),
+                    true,
+                    ObfuscateBelow10Mode::Obfuscate,
+                    1,
+                    &mut rng,
+                )
+                .unwrap();
+            }
+        });
+        assert_eq!(
+            *events.0.lock().unwrap(),
+            ["epsilon=1", "miss", "epsilon=1", "hit"]
+        );
+
+        // with a minimum scale, the epsilon of the applied noise is logged
+        let events = CacheEvents::default();
+        let config = DiffPrivConfig::new()
+            .sensitivity(2.0)
+            .epsilon(1e6)
+            .min_scale(Some(4.0));
+        tracing::subscriber::with_default(events.clone(), || {
+            config.privatize(27, 1, None, &mut rng).unwrap();
+        });
+        assert_eq!(*events.0.lock().unwrap(), ["epsilon=0.5", "none"]);
+    }
+}
@@ -0,0 +1,220 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::fmt;
+use rand::Rng;
+
+use crate::errors::LaplaceError;
+use crate::mechanism::{LaplaceMechanism, Mechanism};
+use crate::{exact_f64, round_parametric, validate_params};
+
+/// Releases a running total under continual observation with the binary tree mechanism, e.g.
+/// the number of cases so far, updated daily. The updates are the leaves of a binary tree, and
+/// the partial sums of its subtrees are noised once. The total after `t` updates is the sum of
+/// at most `log2(t) + 1` noised partial sums, one per set bit of `t`, so the noise grows
+/// logarithmically with the number of updates, while every update is part of at most that
+/// many partial sums.
+///
+/// The total can be released after any update, as often as needed, at no additional privacy
+/// cost, since the noise of each partial sum is sampled only once. With `max_updates` updates,
+/// each partial sum is noised with the scale `levels * sensitivity / epsilon`, where `levels`
+/// is the number of bits of `max_updates`, so that the whole stream of releases is
+/// (epsilon, 0)-differentially private if every increment is bounded by the sensitivity.
+pub struct ContinualCounter {
+    mechanism: LaplaceMechanism,
+    max_updates: u64,
+    updates: u64,
+    // the clear and noise of the partial sums, by level in the binary tree; the noise is
+    // sampled on the first release that includes the partial sum
+    partial_sums: Vec<u64>,
+    noise: Vec<Option<f64>>,
+}
+
+/// Omits the partial sums, which would reveal the clear increments.
+impl fmt::Debug for ContinualCounter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContinualCounter")
+            .field("updates", &self.updates)
+            .field("max_updates", &self.max_updates)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ContinualCounter {
+    /// Creates a counter for at most `max_updates` updates. Returns an error if `max_updates`
+    /// is 0, or if the sensitivity or epsilon are invalid, see `validate_params`.
+    ///
+    /// # Arguments
+    ///
+    /// * `sensitivity` - The largest increment of a single update.
+    /// * `epsilon` - Privacy budget parameter of the whole stream of releases.
+    /// * `max_updates` - The number of updates the counter accepts.
+    pub fn new(sensitivity: f64, epsilon: f64, max_updates: u64) -> Result<Self, LaplaceError> {
+        if max_updates == 0 {
+            return Err(LaplaceError::NonPositiveParameter {
+                name: "max_updates",
+                value: 0.0,
+            });
+        }
+        let levels = (u64::BITS - max_updates.leading_zeros()) as usize;
+        let level_sensitivity = levels as f64 * sensitivity;
+        validate_params(level_sensitivity, epsilon, 1)?;
+        Ok(ContinualCounter {
+            mechanism: LaplaceMechanism::new(level_sensitivity, epsilon),
+            max_updates,
+            updates: 0,
+            partial_sums: vec![0; levels],
+            noise: vec![None; levels],
+        })
+    }
+
+    /// The number of updates so far.
+    pub fn updates(&self) -> u64 {
+        self.updates
+    }
+
+    /// The number of updates the counter accepts.
+    pub fn max_updates(&self) -> u64 {
+        self.max_updates
+    }
+
+    /// Adds the increment of the next update to the total. Returns an error and leaves the
+    /// counter untouched if it already reached `max_updates` updates.
+    pub fn add(&mut self, increment: u64) -> Result<(), LaplaceError> {
+        if self.updates == self.max_updates {
+            return Err(LaplaceError::UpdateLimitReached(self.max_updates));
+        }
+        self.updates += 1;
+        // the new partial sum replaces the partial sums of the levels below it
+        let level = self.updates.trailing_zeros() as usize;
+        let sum = self.partial_sums[..level]
+            .iter()
+            .fold(increment, |sum, &partial_sum| {
+                sum.saturating_add(partial_sum)
+            });
+        for lower in 0..level {
+            self.partial_sums[lower] = 0;
+            self.noise[lower] = None;
+        }
+        self.partial_sums[level] = sum;
+        self.noise[level] = None;
+        Ok(())
+    }
+
+    /// Releases the noised total of all updates so far, rounded to the nearest integer and
+    /// clamped at 0. Releasing again before the next update returns the same total.
+    ///
+    /// # Arguments
+    ///
+    /// * rng - A secure random generator for seeded randomness.
+    ///
+    /// # Returns
+    ///
+    /// The noised total, or an error if the obfuscation failed.
+    pub fn release<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Result<u64, LaplaceError> {
+        let mut total = 0.0;
+        for level in 0..self.partial_sums.len() {
+            if self.updates & (1 << level) == 0 {
+                continue;
+            }
+            let noise = match self.noise[level] {
+                Some(noise) => noise,
+                None => {
+                    let noise = self.mechanism.perturb(0.0, rng)?;
+                    self.noise[level] = Some(noise);
+                    noise
+                }
+            };
+            total += exact_f64(self.partial_sums[level])? + noise;
+        }
+        round_parametric(total, 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_continual_counter_noiseless_total() {
+        let mut rng = rand::thread_rng();
+        let mut counter = ContinualCounter::new(5.0, f64::INFINITY, 100).unwrap();
+        assert_eq!(counter.release(&mut rng).unwrap(), 0);
+        let mut total = 0;
+        for increment in (0..100).map(|update| update % 6) {
+            counter.add(increment).unwrap();
+            total += increment;
+            assert_eq!(counter.release(&mut rng).unwrap(), total);
+        }
+        assert_eq!(counter.updates(), 100);
+        assert!(matches!(
+            counter.add(1),
+            Err(LaplaceError::UpdateLimitReached(100))
+        ));
+        assert_eq!(counter.release(&mut rng).unwrap(), total);
+    }
+
+    #[test]
+    fn test_continual_counter_invalid() {
+        assert!(matches!(
+            ContinualCounter::new(1.0, 1.0, 0),
+            Err(LaplaceError::NonPositiveParameter {
+                name: "max_updates",
+                ..
+            })
+        ));
+        assert!(ContinualCounter::new(1.0, 0.0, 10).is_err());
+        assert!(ContinualCounter::new(-1.0, 1.0, 10).is_err());
+    }
+
+    #[test]
+    fn test_continual_counter_release_repeatable() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut counter = ContinualCounter::new(1.0, 0.5, 64).unwrap();
+        for _ in 0..13 {
+            counter.add(3).unwrap();
+        }
+        let released = counter.release(&mut rng).unwrap();
+        for _ in 0..10 {
+            assert_eq!(counter.release(&mut rng).unwrap(), released);
+        }
+    }
+
+    #[test]
+    fn test_continual_counter_error_logarithmic() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let trials = 50;
+        // mean absolute error after all but the last update, which sums the most partial sums
+        let mut mean_error = |max_updates: u64| {
+            let mut error = 0;
+            for _ in 0..trials {
+                let mut counter = ContinualCounter::new(1.0, 1.0, max_updates).unwrap();
+                for _ in 1..max_updates {
+                    counter.add(1).unwrap();
+                }
+                error += counter.release(&mut rng).unwrap().abs_diff(max_updates - 1);
+            }
+            error as f64 / trials as f64
+        };
+        let small = mean_error(1 << 6);
+        let large = mean_error(1 << 14);
+        // 7 and 15 levels, so the sum of up to 15 noises with scale 15 has a mean absolute
+        // error of at most sqrt(2 * 15) * 15, while 256 times the updates with per-update
+        // noise would have about 16 times the error
+        assert!(large < 30f64.sqrt() * 15.0);
+        assert!(large < 6.0 * small);
+    }
+
+    #[test]
+    fn test_continual_counter_debug_omits_sums() {
+        let mut counter = ContinualCounter::new(1.0, 1.0, 10).unwrap();
+        counter.add(27).unwrap();
+        assert_eq!(
+            format!("{:?}", counter),
+            "ContinualCounter { updates: 1, max_updates: 10, .. }"
+        );
+    }
+}
@@ -1,12 +1,77 @@
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(feature = "std")]
+use statrs::distribution::GeometricError;
+#[cfg(feature = "std")]
 use statrs::distribution::LaplaceError as StatsError;
+#[cfg(feature = "std")]
+use statrs::distribution::NormalError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum LaplaceError {
+    #[cfg(feature = "std")]
     #[error("Unable to create Laplace distribution: {0}")]
     DistributionCreationError(StatsError),
+    #[error("Scale of the distribution must be positive, got {b}")]
+    NonPositiveScale { b: f64 },
+    #[error("Parameter {name} must be finite, got {value}")]
+    NonFiniteParameter { name: &'static str, value: f64 },
+    #[error("Parameter {name} must be a positive finite number, got {value}")]
+    NonPositiveParameter { name: &'static str, value: f64 },
+    #[error("Epsilon must be a positive finite number, got {0}")]
+    InvalidEpsilon(f64),
+    #[error("Sensitivity must be a non-negative finite number, got {0}")]
+    InvalidSensitivity(f64),
+    #[error("Privacy budget exhausted: requested {requested}, remaining {remaining}")]
+    BudgetExhausted { requested: f64, remaining: f64 },
+    #[error("Confidence must be in the open interval (0, 1), got {0}")]
+    InvalidConfidence(f64),
     #[error("Rounding step zero not allowed")]
     InvalidArgRoundingStepZero,
     #[error("Rounding step error: {0}")]
     RoundingStepError(String),
+    #[cfg(feature = "std")]
+    #[error("Unable to create Normal distribution: {0}")]
+    NormalDistributionCreationError(NormalError),
+    #[cfg(feature = "std")]
+    #[error("Unable to create Geometric distribution: {0}")]
+    GeometricDistributionCreationError(GeometricError),
+    #[error("Slice lengths do not match: {0} != {1}")]
+    LengthMismatch(usize, usize),
+    #[error("Invalid domain: Must be None or a positive non-zero number")]
+    InvalidDomain,
+    #[error("Bound must be a positive finite number within the range of the mechanism, got {0}")]
+    InvalidBound(f64),
+    #[error("Delta must be in the open interval (0, 1), got {0}")]
+    InvalidDelta(f64),
+    #[error("Denominator must not be zero")]
+    ZeroDenominator,
+    #[error("Input must not be empty")]
+    EmptyInput,
+    #[error("Unknown mode {0}, expected one of zero, ten or obfuscate")]
+    UnknownMode(String),
+    #[error("Invalid input bounds: lower bound {0} exceeds upper bound {1}")]
+    InvalidInputBounds(u64, u64),
+    #[error("Cache capacity must be positive")]
+    InvalidCacheCapacity,
+    #[error("Invalid output bounds: lower bound {0} must be less than upper bound {1}")]
+    InvalidOutputBounds(f64, f64),
+    #[error("No sample within the bounds after {0} attempts")]
+    ResamplingExhausted(usize),
+    #[error("Value {0} exceeds 2^53 and can't be obfuscated without losing precision")]
+    PrecisionLoss(u64),
+    #[error("Quantile must be in the interval [0, 1], got {0}")]
+    InvalidQuantile(f64),
+    #[error("Category {0} must be less than the number of categories {1}")]
+    InvalidCategory(usize, usize),
+    #[error("Rounding step {rounding_step} is too large for values up to {expected_max_value}")]
+    StepTooLargeRelativeToValue {
+        rounding_step: usize,
+        expected_max_value: u64,
+    },
+    #[error("Continual counter reached its limit of {0} updates")]
+    UpdateLimitReached(u64),
+    #[error("Bin {bin} is out of range, the cache holds bins below {max_bins}")]
+    BinOutOfRange { bin: usize, max_bins: usize },
 }
@@ -0,0 +1,101 @@
+use rand::Rng;
+
+use crate::errors::LaplaceError;
+use crate::mechanism::{LaplaceMechanism, Mechanism};
+use crate::{exact_f64, round_parametric};
+
+/// Extends iterators over clear values with lazy obfuscation.
+pub trait PrivatizeExt: Iterator<Item = u64> + Sized {
+    /// Lazily obfuscates each value like `privatize`.
+    ///
+    /// # Arguments
+    ///
+    /// * `sensitivity` - Sensitivity of query.
+    /// * `epsilon` - Privacy budget parameter.
+    /// * `rounding_step` - Rounding to the given number is performed.
+    /// * `rng` - A secure random generator for seeded randomness.
+    ///
+    /// # Returns
+    ///
+    /// An iterator over the obfuscated values, or the errors if the obfuscation failed.
+    fn privatize<R: Rng + ?Sized>(
+        self,
+        sensitivity: f64,
+        epsilon: f64,
+        rounding_step: usize,
+        rng: &mut R,
+    ) -> Privatize<'_, Self, R> {
+        Privatize {
+            iter: self,
+            mechanism: LaplaceMechanism::new(sensitivity, epsilon),
+            rounding_step,
+            rng,
+        }
+    }
+}
+
+impl<I: Iterator<Item = u64>> PrivatizeExt for I {}
+
+/// An iterator over obfuscated values, created by `PrivatizeExt::privatize`.
+pub struct Privatize<'a, I, R: ?Sized> {
+    iter: I,
+    mechanism: LaplaceMechanism,
+    rounding_step: usize,
+    rng: &'a mut R,
+}
+
+impl<I: Iterator<Item = u64>, R: Rng + ?Sized> Iterator for Privatize<'_, I, R> {
+    type Item = Result<u64, LaplaceError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.iter.next()?;
+        Some(
+            exact_f64(value)
+                .and_then(|value| self.mechanism.perturb(value, self.rng))
+                .and_then(|obfuscated_value| {
+                    round_parametric(obfuscated_value, self.rounding_step)
+                }),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::privatize;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_privatize_ext_matches_loop() {
+        let values = [0, 5, 27, 27, 1000];
+        let mut rng = StdRng::seed_from_u64(42);
+        let obfuscated: Vec<u64> = values
+            .into_iter()
+            .privatize(1.0, 0.1, 5, &mut rng)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut expected = Vec::new();
+        for value in values {
+            expected.push(privatize(value, 1.0, 0.1, 5, &mut rng).unwrap());
+        }
+        assert_eq!(obfuscated, expected);
+    }
+
+    #[test]
+    fn test_privatize_ext_lazy_errors() {
+        let mut rng = rand::thread_rng();
+        let mut obfuscated = (0..3).privatize(1.0, 0.0, 1, &mut rng);
+        assert_eq!(obfuscated.size_hint(), (3, Some(3)));
+        assert!(matches!(
+            obfuscated.next(),
+            Some(Err(LaplaceError::InvalidEpsilon(_)))
+        ));
+    }
+}
@@ -1,29 +1,533 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod analysis;
+pub mod budget;
+#[cfg(feature = "std")]
+pub mod config;
+pub mod continual;
 pub mod errors;
+pub mod iter;
+pub mod mechanism;
+#[cfg(feature = "std")]
+pub mod obfuscator;
+pub mod postprocessing;
+pub mod prelude;
+pub mod response;
+pub mod selection;
+#[cfg(feature = "serde")]
+mod serialization;
+#[cfg(feature = "std")]
+pub mod sync_cache;
 
+#[cfg(not(feature = "std"))]
+use alloc::format;
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+#[cfg(all(feature = "bulk", not(feature = "std")))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
 use anyhow::Result;
+use core::fmt;
+use core::str::FromStr;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 use rand::distributions::Distribution;
-use statrs::distribution::Laplace;
-use std::collections::HashMap;
+use rand::distributions::Open01;
+use rand::Rng;
+#[cfg(feature = "std")]
+use statrs::distribution::Geometric;
+#[cfg(feature = "std")]
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
 
+#[cfg(feature = "std")]
+use crate::config::DiffPrivConfig;
 use crate::errors::LaplaceError;
+#[cfg(feature = "std")]
+use crate::mechanism::GaussianMechanism;
+use crate::mechanism::{LaplaceMechanism, Mechanism};
 
 // obfuscation cache
-type Sensitivity = usize;
+// bit patterns of the sensitivity and epsilon, since f64 isn't hashable
+#[cfg(feature = "std")]
+type Sensitivity = u64;
+#[cfg(feature = "std")]
+type Epsilon = u64;
+#[cfg(feature = "std")]
 type Count = u64;
 pub type Bin = usize;
+#[cfg(feature = "std")]
+type CacheKey = (Sensitivity, Epsilon, Count, Bin);
+
+#[cfg(feature = "std")]
+#[derive(Default, Clone)]
 pub struct ObfCache {
-    pub cache: HashMap<(Sensitivity, Count, Bin), u64>,
+    cache: HashMap<CacheKey, u64>,
+    // least-recently-used bookkeeping, only maintained if max_entries is set
+    max_entries: Option<usize>,
+    tick: u64,
+    last_used: HashMap<CacheKey, u64>,
+    recency: BTreeMap<u64, CacheKey>,
+    // when each entry was inserted, for expiring cached noise
+    inserted_at: HashMap<CacheKey, Instant>,
+    max_bins: Option<Bin>,
+    hits: u64,
+    misses: u64,
+}
+
+/// Summarizes the size and statistics of the cache instead of listing every entry.
+#[cfg(feature = "std")]
+impl fmt::Debug for ObfCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObfCache")
+            .field("len", &self.len())
+            .field("max_entries", &self.max_entries)
+            .field("hits", &self.hits)
+            .field("misses", &self.misses)
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl ObfCache {
+    /// Creates an empty obfuscation cache without a size limit.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty obfuscation cache holding at most `max_entries` entries. When the
+    /// cache is full, the least recently used entry is evicted. Returns an error if
+    /// `max_entries` is 0, since such a cache couldn't hold any entry.
+    pub fn with_capacity(max_entries: usize) -> Result<Self, LaplaceError> {
+        if max_entries == 0 {
+            return Err(LaplaceError::InvalidCacheCapacity);
+        }
+        Ok(ObfCache {
+            max_entries: Some(max_entries),
+            ..Self::default()
+        })
+    }
+
+    /// Creates an unbounded obfuscation cache preloaded with the given
+    /// `(sensitivity, epsilon, count, bin)` keys and obfuscated values, see `preload`.
+    pub fn with_entries<I>(entries: I) -> Self
+    where
+        I: IntoIterator<Item = ((f64, f64, Count, Bin), u64)>,
+    {
+        let mut obf_cache = ObfCache::new();
+        for ((sensitivity, epsilon, count, bin), value) in entries {
+            obf_cache.preload(sensitivity, epsilon, count, bin, value);
+        }
+        obf_cache
+    }
+
+    /// Inserts the given obfuscated value for the given key, e.g. for testing or to reproduce
+    /// the values of another system. Obfuscating the count of the bin with the same
+    /// sensitivity and epsilon then returns the value verbatim, without sampling noise.
+    pub fn preload(&mut self, sensitivity: f64, epsilon: f64, count: Count, bin: Bin, value: u64) {
+        self.insert(
+            (sensitivity.to_bits(), epsilon.to_bits(), count, bin),
+            value,
+        );
+    }
+
+    /// Restricts the bins of the cache to bins below `max_bins`, so that obfuscating with an
+    /// out-of-range bin, e.g. a count passed as the bin by mistake, returns an error instead of
+    /// silently caching a value for it. Preloaded and merged entries aren't checked.
+    pub fn with_max_bins(mut self, max_bins: Bin) -> Self {
+        self.max_bins = Some(max_bins);
+        self
+    }
+
+    /// The maximum number of entries, or None if the cache is unbounded.
+    pub fn max_entries(&self) -> Option<usize> {
+        self.max_entries
+    }
+
+    /// The number of bins the cache holds values for, or None if all bins are allowed.
+    pub fn max_bins(&self) -> Option<Bin> {
+        self.max_bins
+    }
+
+    /// The number of cached obfuscated values.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Returns true if the cache holds no obfuscated values.
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Estimates the memory used by the cache in bytes, e.g. to choose `max_entries` for a
+    /// memory budget. Each entry takes its key and obfuscated value, its insertion time and,
+    /// in a cache with a size limit, its least-recently-used bookkeeping, plus a byte of
+    /// overhead per hash map entry. Unused capacity of the maps isn't included.
+    pub fn approx_memory_bytes(&self) -> usize {
+        use core::mem::size_of;
+
+        let mut entry_bytes = size_of::<(CacheKey, u64)>() + size_of::<(CacheKey, Instant)>() + 2;
+        if self.max_entries.is_some() {
+            entry_bytes += size_of::<(CacheKey, u64)>() + 1 + size_of::<(u64, CacheKey)>();
+        }
+        size_of::<Self>() + self.len() * entry_bytes
+    }
+
+    /// Removes all cached obfuscated values, keeping the size limit.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.last_used.clear();
+        self.recency.clear();
+        self.inserted_at.clear();
+    }
+
+    /// Removes the cached obfuscated values inserted longer than `max_age` ago, so that their
+    /// noise is sampled anew on the next obfuscation, e.g. to rotate the noise daily.
+    ///
+    /// # Returns
+    ///
+    /// The number of removed values.
+    pub fn purge_older_than(&mut self, max_age: Duration) -> usize {
+        let stale: Vec<CacheKey> = self
+            .inserted_at
+            .iter()
+            .filter(|(_, inserted_at)| inserted_at.elapsed() > max_age)
+            .map(|(&key, _)| key)
+            .collect();
+        for key in &stale {
+            self.remove(key);
+        }
+        stale.len()
+    }
+
+    /// Inserts all entries of the other cache, e.g. of a worker that obfuscated a shard of the
+    /// data. The policy decides which value is kept for a key cached in both caches, see
+    /// `MergePolicy`. The hit and miss counters of both caches are added up, and the entries
+    /// keep their insertion time for `purge_older_than`.
+    pub fn merge(&mut self, other: ObfCache, policy: MergePolicy) {
+        for (key, value) in other.cache {
+            if policy == MergePolicy::KeepExisting && self.cache.contains_key(&key) {
+                continue;
+            }
+            self.insert(key, value);
+            if let Some(&inserted_at) = other.inserted_at.get(&key) {
+                self.inserted_at.insert(key, inserted_at);
+            }
+        }
+        self.hits += other.hits;
+        self.misses += other.misses;
+    }
+
+    /// Lists every cached obfuscated value as a record, e.g. as a manifest of the obfuscations
+    /// of a release for audits. The records are sorted by sensitivity, epsilon, count and bin,
+    /// so that the same entries always give the same manifest. The cache can be rebuilt from
+    /// the manifest with `with_entries`.
+    pub fn to_manifest(&self) -> Vec<CacheRecord> {
+        let mut entries: Vec<_> = self.cache.iter().collect();
+        entries.sort_unstable_by_key(|&(&key, _)| key);
+        entries
+            .into_iter()
+            .map(
+                |(&(sensitivity, epsilon, count, bin), &value)| CacheRecord {
+                    sensitivity: f64::from_bits(sensitivity),
+                    epsilon: f64::from_bits(epsilon),
+                    count,
+                    bin,
+                    value,
+                },
+            )
+            .collect()
+    }
+
+    /// Returns the cached obfuscated value for the given key without obfuscating on a miss
+    /// and without marking the entry as recently used.
+    pub fn peek(&self, sensitivity: f64, epsilon: f64, count: Count, bin: Bin) -> Option<u64> {
+        self.cache
+            .get(&(sensitivity.to_bits(), epsilon.to_bits(), count, bin))
+            .copied()
+    }
+
+    /// The number of obfuscations answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// The number of obfuscations that had to sample a new value.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// The fraction of obfuscations answered from the cache, or 0 if there were none.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / lookups as f64
+    }
+
+    /// Resets the hit and miss counters, e.g. for periodic reporting.
+    pub fn reset_stats(&mut self) {
+        self.hits = 0;
+        self.misses = 0;
+    }
+
+    fn check_bin(&self, bin: Bin) -> Result<(), LaplaceError> {
+        match self.max_bins {
+            Some(max_bins) if bin >= max_bins => Err(LaplaceError::BinOutOfRange { bin, max_bins }),
+            _ => Ok(()),
+        }
+    }
+
+    fn get(&mut self, key: &CacheKey) -> Option<u64> {
+        let Some(&value) = self.cache.get(key) else {
+            self.misses += 1;
+            return None;
+        };
+        self.hits += 1;
+        self.touch(key);
+        Some(value)
+    }
+
+    fn insert(&mut self, key: CacheKey, value: u64) {
+        if let Some(max_entries) = self.max_entries {
+            while !self.cache.contains_key(&key) && self.cache.len() >= max_entries {
+                let Some((_, oldest)) = self.recency.pop_first() else {
+                    break;
+                };
+                self.remove(&oldest);
+            }
+        }
+        self.cache.insert(key, value);
+        self.inserted_at.insert(key, Instant::now());
+        self.touch(&key);
+    }
+
+    fn remove(&mut self, key: &CacheKey) {
+        self.cache.remove(key);
+        self.inserted_at.remove(key);
+        if let Some(last_used) = self.last_used.remove(key) {
+            self.recency.remove(&last_used);
+        }
+    }
+
+    fn touch(&mut self, key: &CacheKey) {
+        if self.max_entries.is_none() {
+            return;
+        }
+        self.tick += 1;
+        if let Some(previous) = self.last_used.insert(*key, self.tick) {
+            self.recency.remove(&previous);
+        }
+        self.recency.insert(self.tick, *key);
+    }
+}
+
+/// A single entry of the obfuscation cache: the obfuscated value cached for the count of a
+/// bin obfuscated with the given sensitivity and epsilon, see `ObfCache::to_manifest`.
+#[cfg(feature = "std")]
+#[derive(PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CacheRecord {
+    pub sensitivity: f64,
+    pub epsilon: f64,
+    pub count: Count,
+    pub bin: Bin,
+    pub value: u64,
+}
+
+/// Which value `ObfCache::merge` keeps for a key cached in both caches. The two values were
+/// obfuscated with independent noise, so either is valid, but only one of them may ever be
+/// released for the key, since releasing both would average out the noise.
+#[cfg(feature = "std")]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum MergePolicy {
+    /// Keep the value of the cache merged into, e.g. if it may already have been released.
+    KeepExisting,
+    /// Take the value of the merged cache.
+    Overwrite,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone)]
+/// How values below 10 are obfuscated. Superseded by `SmallCountPolicy`, which allows other
+/// thresholds; `Zero`, `Ten` and `Obfuscate` map to a threshold of 10 with the suppression
+/// modes `Zero`, `Clamp(10)` and `Obfuscate` respectively.
+///
+/// To migrate, replace `DiffPrivConfig::below_10_mode(ObfuscateBelow10Mode::Ten)` with
+/// `DiffPrivConfig::small_count_policy(SmallCountPolicy { threshold: 10, mode:
+/// SuppressionMode::Clamp(10) })`, or convert an existing mode with `SmallCountPolicy::from`.
+/// The functions taking a mode, e.g. `get_from_cache_or_privatize`, apply it through the
+/// equivalent policy.
+#[deprecated(note = "use SmallCountPolicy")]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum ObfuscateBelow10Mode {
     Zero,
     Ten,
     Obfuscate,
 }
 
+#[allow(deprecated)]
+impl FromStr for ObfuscateBelow10Mode {
+    type Err = LaplaceError;
+
+    /// Parses the mode from "zero", "ten" or "obfuscate", ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "zero" => Ok(ObfuscateBelow10Mode::Zero),
+            "ten" => Ok(ObfuscateBelow10Mode::Ten),
+            "obfuscate" => Ok(ObfuscateBelow10Mode::Obfuscate),
+            _ => Err(LaplaceError::UnknownMode(s.to_string())),
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl fmt::Display for ObfuscateBelow10Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ObfuscateBelow10Mode::Zero => "zero",
+            ObfuscateBelow10Mode::Ten => "ten",
+            ObfuscateBelow10Mode::Obfuscate => "obfuscate",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The outcome of `privatize_count_with_suppression`, distinguishing suppressed values from
+/// obfuscated values, e.g. to report "<10" instead of 0.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ObfuscationResult {
+    /// The value was obfuscated.
+    Obfuscated(u64),
+    /// The value was zero and zero counts aren't obfuscated.
+    SuppressedZero,
+    /// The value was below 10 and reported as 0.
+    SuppressedBelowThreshold,
+    /// The value was below 10 and reported as 10.
+    ClampedToTen,
+}
+
+impl ObfuscationResult {
+    /// The value reported for this outcome, as returned by `get_from_cache_or_privatize`.
+    pub fn value(&self) -> u64 {
+        match self {
+            ObfuscationResult::Obfuscated(value) => *value,
+            ObfuscationResult::SuppressedZero => 0,
+            ObfuscationResult::SuppressedBelowThreshold => 0,
+            ObfuscationResult::ClampedToTen => 10,
+        }
+    }
+
+    /// Renders this outcome for presentation like `Display`, with values suppressed below the
+    /// given threshold rendered as "<{threshold}" instead of "<10".
+    pub fn to_display_string(&self, threshold: u64) -> String {
+        match self {
+            ObfuscationResult::SuppressedBelowThreshold => format!("<{threshold}"),
+            _ => self.to_string(),
+        }
+    }
+}
+
+/// Renders values suppressed below 10 as "<10", and all other outcomes as the reported value,
+/// e.g. "0" for suppressed zero counts.
+impl fmt::Display for ObfuscationResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObfuscationResult::SuppressedBelowThreshold => f.write_str("<10"),
+            _ => write!(f, "{}", self.value()),
+        }
+    }
+}
+
+/// How values below the threshold of a `SmallCountPolicy` are reported.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum SuppressionMode {
+    /// Report 0.
+    Zero,
+    /// Report the given value, e.g. the threshold.
+    Clamp(u64),
+    /// Obfuscate like any other value. There is deliberately no separate mechanism for small
+    /// counts, e.g. one truncated to `[0, 2 * threshold]`: choosing the mechanism by the clear
+    /// value would reveal from the range of the output on which side of the threshold it is.
+    Obfuscate,
+}
+
+/// Small-cell suppression policy, applied to values below `threshold`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SmallCountPolicy {
+    pub threshold: u64,
+    pub mode: SuppressionMode,
+}
+
+impl SmallCountPolicy {
+    /// Returns the value reported instead of obfuscating, if the policy applies to the value.
+    pub fn suppress(&self, value: u64) -> Option<u64> {
+        if value >= self.threshold {
+            return None;
+        }
+        match self.mode {
+            SuppressionMode::Zero => Some(0),
+            SuppressionMode::Clamp(clamped) => Some(clamped),
+            SuppressionMode::Obfuscate => None,
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl From<ObfuscateBelow10Mode> for SmallCountPolicy {
+    fn from(mode: ObfuscateBelow10Mode) -> Self {
+        let mode = match mode {
+            ObfuscateBelow10Mode::Zero => SuppressionMode::Zero,
+            ObfuscateBelow10Mode::Ten => SuppressionMode::Clamp(10),
+            ObfuscateBelow10Mode::Obfuscate => SuppressionMode::Obfuscate,
+        };
+        SmallCountPolicy {
+            threshold: 10,
+            mode,
+        }
+    }
+}
+
+/// Determines to which multiple of the rounding step a value is rounded.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RoundingMode {
+    /// Round to the nearest multiple.
+    Nearest,
+    /// Round down to the lower multiple, e.g. to avoid over-reporting.
+    Floor,
+    /// Round up to the upper multiple.
+    Ceil,
+    /// Round to the lower or upper multiple at random, with a probability proportional to the
+    /// proximity of the value to that multiple. Unlike the other modes, this leaves the
+    /// expected value unbiased.
+    Stochastic,
+}
+
+/// Determines whether a value is rounded to the rounding step before or after adding noise.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RoundingOrder {
+    /// Round the clear value to the step, e.g. to coarsen the input to a grid, then add noise
+    /// and round again, unless rounding is disabled.
+    BeforeNoise,
+    /// Add noise to the clear value, then round.
+    #[default]
+    AfterNoise,
+}
+
 /// Obfuscates the given value using a random sampled value from a Laplace distribution with
-/// given delta and epsilon parameters, and bin to which the value belongs. The
+/// given sensitivity and epsilon parameters, and bin to which the value belongs. The
 /// obfuscate_zero flag indicates whether only positive values should be obfuscated or all
 /// values, including zero. The rounding_step determines the granularity of the rounding. If
 /// obf_cache_option is not None, the function checks the cache for a pre-computed value before
@@ -32,7 +536,7 @@ pub enum ObfuscateBelow10Mode {
 /// # Arguments
 ///
 /// * value - The input value to be obfuscated.
-/// * delta - Sensitivity.
+/// * sensitivity - Sensitivity of query.
 /// * epsilon - Privacy budget parameter.
 /// * bin - The bin that the value belongs to.
 /// * obf_cache_option - An option that represents the obfuscation cache.
@@ -45,59 +549,286 @@ pub enum ObfuscateBelow10Mode {
 ///
 /// The obfuscated value, rounded to the nearest multiple of the rounding_step, or an error if the
 /// obfuscation failed.
-pub fn get_from_cache_or_privatize(
+#[cfg(feature = "std")]
+#[allow(clippy::too_many_arguments, deprecated)]
+#[must_use = "the obfuscation may have failed"]
+pub fn get_from_cache_or_privatize<R: Rng + ?Sized>(
     value: u64,
-    delta: f64,
+    sensitivity: f64,
     epsilon: f64,
     bin: Bin,
     obf_cache_option: Option<&mut ObfCache>,
     obfuscate_zero: bool,
     obfuscate_below_10_mode: ObfuscateBelow10Mode,
     rounding_step: usize,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut R,
 ) -> Result<u64, LaplaceError> {
-    let obfuscated: u64 = match obf_cache_option {
-        None => privatize(value, delta, epsilon, rounding_step, rng).unwrap(),
-        Some(obf_cache) => {
-            if !obfuscate_zero && value == 0 {
-                return Ok(0);
-            }
+    DiffPrivConfig::new()
+        .sensitivity(sensitivity)
+        .epsilon(epsilon)
+        .rounding_step(rounding_step)
+        .obfuscate_zero(obfuscate_zero)
+        .small_count_policy(obfuscate_below_10_mode.into())
+        .privatize(value, bin, obf_cache_option, rng)
+}
 
-            if value < 10 {
-                if obfuscate_below_10_mode == ObfuscateBelow10Mode::Zero {
-                    return Ok(0);
-                }
-                if obfuscate_below_10_mode == ObfuscateBelow10Mode::Ten {
-                    return Ok(10);
-                }
-            }
+/// Obfuscates the given value like `get_from_cache_or_privatize`, but reports whether the
+/// value was suppressed instead of obfuscated.
+///
+/// # Arguments
+///
+/// * value - The input value to be obfuscated.
+/// * sensitivity - Sensitivity of query.
+/// * epsilon - Privacy budget parameter.
+/// * bin - The bin that the value belongs to.
+/// * obf_cache_option - An option that represents the obfuscation cache.
+/// * obfuscate_zero - A flag indicating whether zero counts should be obfuscated.
+/// * below_10_obfuscation_mode: 0 - return 0, 1 - return 10, 2 - obfuscate using Laplace distribution and rounding
+/// * rounding_step - The granularity of the rounding.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated value or the reason for the suppression, or an error if the obfuscation
+/// failed.
+#[cfg(feature = "std")]
+#[allow(clippy::too_many_arguments, deprecated)]
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_count_with_suppression<R: Rng + ?Sized>(
+    value: u64,
+    sensitivity: f64,
+    epsilon: f64,
+    bin: Bin,
+    obf_cache_option: Option<&mut ObfCache>,
+    obfuscate_zero: bool,
+    obfuscate_below_10_mode: ObfuscateBelow10Mode,
+    rounding_step: usize,
+    rng: &mut R,
+) -> Result<ObfuscationResult, LaplaceError> {
+    if !obfuscate_zero && value == 0 {
+        return Ok(ObfuscationResult::SuppressedZero);
+    }
+    let policy = SmallCountPolicy::from(obfuscate_below_10_mode);
+    if value < policy.threshold {
+        match policy.mode {
+            SuppressionMode::Zero => return Ok(ObfuscationResult::SuppressedBelowThreshold),
+            SuppressionMode::Clamp(_) => return Ok(ObfuscationResult::ClampedToTen),
+            SuppressionMode::Obfuscate => {}
+        }
+    }
+    let obfuscated = DiffPrivConfig::new()
+        .sensitivity(sensitivity)
+        .epsilon(epsilon)
+        .rounding_step(rounding_step)
+        .obfuscate_zero(obfuscate_zero)
+        .small_count_policy(policy)
+        .privatize(value, bin, obf_cache_option, rng)?;
+    Ok(ObfuscationResult::Obfuscated(obfuscated))
+}
+
+/// Obfuscates each of the given values with `get_from_cache_or_privatize`, reusing the
+/// same obfuscation cache and `LaplaceMechanism` for the whole batch. Values and bins are
+/// matched by position.
+///
+/// # Arguments
+///
+/// * values - The input values to be obfuscated.
+/// * sensitivity - Sensitivity of query.
+/// * epsilon - Privacy budget parameter.
+/// * bins - The bins that the values belong to, one per value.
+/// * obf_cache_option - An option that represents the obfuscation cache.
+/// * obfuscate_zero - A flag indicating whether zero counts should be obfuscated.
+/// * below_10_obfuscation_mode: 0 - return 0, 1 - return 10, 2 - obfuscate using Laplace distribution and rounding
+/// * rounding_step - The granularity of the rounding.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated values in the order of the input values, or an error if the slice lengths
+/// do not match, the parameters are invalid, see `validate_params`, or the obfuscation
+/// failed.
+#[cfg(feature = "std")]
+#[allow(clippy::too_many_arguments, deprecated)]
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_batch<R: Rng + ?Sized>(
+    values: &[u64],
+    sensitivity: f64,
+    epsilon: f64,
+    bins: &[Bin],
+    obf_cache_option: Option<&mut ObfCache>,
+    obfuscate_zero: bool,
+    obfuscate_below_10_mode: ObfuscateBelow10Mode,
+    rounding_step: usize,
+    rng: &mut R,
+) -> Result<Vec<u64>, LaplaceError> {
+    if values.len() != bins.len() {
+        return Err(LaplaceError::LengthMismatch(values.len(), bins.len()));
+    }
+    validate_params(sensitivity, epsilon, rounding_step)?;
+    DiffPrivConfig::new()
+        .sensitivity(sensitivity)
+        .epsilon(epsilon)
+        .rounding_step(rounding_step)
+        .obfuscate_zero(obfuscate_zero)
+        .small_count_policy(obfuscate_below_10_mode.into())
+        .privatize_batch(values, bins, obf_cache_option, rng)
+}
 
-            let sensitivity: usize = delta.round() as usize;
+/// Obfuscates each count of the given histogram with `get_from_cache_or_privatize`, using
+/// the bin of the count as the bin of the obfuscation cache. The counts are obfuscated in
+/// the order of their bins, so that a seeded random generator gives reproducible results.
+///
+/// # Arguments
+///
+/// * histogram - The input counts to be obfuscated, by bin.
+/// * sensitivity - Sensitivity of query.
+/// * epsilon - Privacy budget parameter.
+/// * obf_cache_option - An option that represents the obfuscation cache.
+/// * obfuscate_zero - A flag indicating whether zero counts should be obfuscated.
+/// * below_10_obfuscation_mode: 0 - return 0, 1 - return 10, 2 - obfuscate using Laplace distribution and rounding
+/// * rounding_step - The granularity of the rounding.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated histogram with the same bins, or an error if the obfuscation failed.
+#[cfg(feature = "std")]
+#[allow(clippy::too_many_arguments, deprecated)]
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_histogram<R: Rng + ?Sized>(
+    histogram: &HashMap<Bin, u64>,
+    sensitivity: f64,
+    epsilon: f64,
+    mut obf_cache_option: Option<&mut ObfCache>,
+    obfuscate_zero: bool,
+    obfuscate_below_10_mode: ObfuscateBelow10Mode,
+    rounding_step: usize,
+    rng: &mut R,
+) -> Result<HashMap<Bin, u64>, LaplaceError> {
+    let mut bins: Vec<Bin> = histogram.keys().copied().collect();
+    bins.sort_unstable();
+    bins.into_iter()
+        .map(|bin| {
+            let obfuscated = get_from_cache_or_privatize(
+                histogram[&bin],
+                sensitivity,
+                epsilon,
+                bin,
+                obf_cache_option.as_deref_mut(),
+                obfuscate_zero,
+                obfuscate_below_10_mode.clone(),
+                rounding_step,
+                rng,
+            )?;
+            Ok((bin, obfuscated))
+        })
+        .collect()
+}
 
-            let obfuscated: u64 = match obf_cache.cache.get(&(sensitivity, value, bin)) {
-                Some(obfuscated_reference) => *obfuscated_reference,
-                None => {
-                    let obfuscated_value =
-                        privatize(value, delta, epsilon, rounding_step, rng).unwrap();
+/// Obfuscates a vector-valued query, e.g. a histogram released at once, with the (epsilon, 0)
+/// laplacian mechanism calibrated to the L1 sensitivity of the whole vector. Each element is
+/// perturbed with independent noise of scale `l1_sensitivity / epsilon` and rounded to the
+/// nearest integer.
+///
+/// Unlike obfuscating each element with its own sensitivity, e.g. with `privatize_batch`,
+/// which spends the privacy budget once per element, the whole vector is released with a
+/// single privacy budget `epsilon`. For a histogram where each record contributes to one bin,
+/// the L1 sensitivity is 1; if a record can contribute to `k` bins, it is `k`.
+///
+/// # Arguments
+///
+/// * `values` - Clear values of the vector query.
+/// * `l1_sensitivity` - L1 sensitivity of the whole vector.
+/// * `epsilon` - Privacy budget parameter for the whole vector.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated values in the order of the input values, or an error if the obfuscation
+/// failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_histogram_l1<R: Rng + ?Sized>(
+    values: &[u64],
+    l1_sensitivity: f64,
+    epsilon: f64,
+    rng: &mut R,
+) -> Result<Vec<u64>, LaplaceError> {
+    let mechanism = LaplaceMechanism::new(l1_sensitivity, epsilon);
+    values
+        .iter()
+        .map(|&value| {
+            let obfuscated_value = mechanism.perturb(exact_f64(value)?, rng)?;
+            round_parametric(obfuscated_value, 1)
+        })
+        .collect()
+}
 
-                    obf_cache
-                        .cache
-                        .insert((sensitivity, value, bin), obfuscated_value);
-                    obfuscated_value
+/// Obfuscates each of the given values with `privatize`, using the sensitivity and privacy
+/// budget parameter given for its bin. Values, sensitivities and epsilons are matched by position.
+///
+/// # Arguments
+///
+/// * `values` - Clear values to permute.
+/// * `sensitivities` - Sensitivities, one per value.
+/// * `epsilons` - Privacy budget parameters, one per value.
+/// * `rounding_step` - Rounding to the given number is performed.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated values in the order of the input values, or an error if the slice lengths
+/// do not match or the obfuscation failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_batch_per_bin<R: Rng + ?Sized>(
+    values: &[u64],
+    sensitivities: &[f64],
+    epsilons: &[f64],
+    rounding_step: usize,
+    rng: &mut R,
+) -> Result<Vec<u64>, LaplaceError> {
+    if values.len() != sensitivities.len() {
+        return Err(LaplaceError::LengthMismatch(
+            values.len(),
+            sensitivities.len(),
+        ));
+    }
+    if values.len() != epsilons.len() {
+        return Err(LaplaceError::LengthMismatch(values.len(), epsilons.len()));
+    }
+    // consecutive values with the same parameters, typically all, share one mechanism
+    let mut mechanism: Option<LaplaceMechanism> = None;
+    values
+        .iter()
+        .zip(sensitivities)
+        .zip(epsilons)
+        .map(|((&value, &sensitivity), &epsilon)| {
+            let mechanism = match &mut mechanism {
+                Some(mechanism)
+                    if mechanism.sensitivity() == sensitivity && mechanism.epsilon() == epsilon =>
+                {
+                    mechanism
+                }
+                slot => {
+                    validate_params(sensitivity, epsilon, rounding_step)?;
+                    slot.insert(LaplaceMechanism::new(sensitivity, epsilon))
                 }
             };
-            obfuscated
-        }
-    };
-    Ok(obfuscated)
+            privatize_with_mechanism(value, mechanism, rounding_step, rng)
+        })
+        .collect()
 }
 
-/// Performs the actual perturbation of a value with the (epsilon, 0) laplacian
-/// mechanism and rounds the result to the nearest step position.
+/// Obfuscates each of the given values with the laplacian mechanism like `privatize`, but
+/// samples the noise for all values in bulk: the random bits are generated at once, and the
+/// inverse Laplace CDF is applied to them in a tight loop that the compiler can vectorize.
+/// The noise follows the same distribution as the noise of `privatize`, sampled from the open
+/// interval like `LaplaceMechanism::new_secure`, but the values differ for the same seed.
+/// In the `batch` group of `cargo bench --features bulk`, obfuscating 1 million values took
+/// 33 to 38 ms, against 40 ms with `privatize_batch_per_bin`, i.e. about 5 to 20% less.
 ///
 /// # Arguments
 ///
-/// * `value` - Clear value to permute.
+/// * `values` - Clear values to permute.
 /// * `sensitivity` - Sensitivity of query.
 /// * `epsilon` - Privacy budget parameter.
 /// * `rounding_step` - Rounding to the given number is performed.
@@ -105,165 +836,3666 @@ pub fn get_from_cache_or_privatize(
 ///
 /// # Returns
 ///
-/// The obfuscated value , or an error if the obfuscation failed.
-pub fn privatize(
-    value: u64,
+/// The obfuscated values in the order of the input values, or an error if the parameters are
+/// invalid, see `validate_params`, or the obfuscation failed.
+#[cfg(feature = "bulk")]
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_bulk<R: Rng + ?Sized>(
+    values: &[u64],
     sensitivity: f64,
     epsilon: f64,
     rounding_step: usize,
-    rng: &mut rand::rngs::ThreadRng,
-) -> Result<u64, LaplaceError> {
-    let obfuscated_value = value as f64 + laplace(0.0, sensitivity / epsilon, rng).unwrap();
-    round_parametric(obfuscated_value, rounding_step)
+    rng: &mut R,
+) -> Result<Vec<u64>, LaplaceError> {
+    validate_params(sensitivity, epsilon, rounding_step)?;
+    let scale = if epsilon == f64::INFINITY {
+        0.0
+    } else {
+        laplace_scale(sensitivity, epsilon)?
+    };
+    let noise = sample_laplace_bulk(scale, values.len(), rng);
+    values
+        .iter()
+        .zip(noise)
+        .map(|(&value, noise)| round_parametric(exact_f64(value)? + noise, rounding_step))
+        .collect()
 }
 
-/// Rounds the value to the nearest multiple of the step parameter.
+/// Obfuscates each of the given values with `privatize` in parallel. The values are split
+/// into chunks of `chunk_size`, and each chunk is obfuscated with its own ChaCha20 stream,
+/// selected by the chunk index, of the generator seeded from `seed`, so results are
+/// reproducible regardless of thread scheduling and don't overlap across seeds.
+///
+/// Since a mutable cache can't be shared across threads, this path is cache-less.
 ///
 /// # Arguments
 ///
-/// * `value` - The value to be rounded.
-/// * `stepParameter` - The step to round to, for example, 1, 5, or 10.
+/// * `values` - Clear values to permute.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * `rounding_step` - Rounding to the given number is performed.
+/// * `seed` - Base seed from which the random generator of each chunk is derived.
+/// * `chunk_size` - Number of values obfuscated with the same random generator.
 ///
 /// # Returns
 ///
-/// Returns the rounded value, or an error if the rounding failed.
-fn round_parametric(value: f64, step_parameter: usize) -> Result<u64, LaplaceError> {
-    if step_parameter == 0 {
-        return Err(LaplaceError::InvalidArgRoundingStepZero);
-    }
-    Ok((value / step_parameter as f64).round() as u64 * step_parameter as u64)
+/// The obfuscated values in the order of the input values, or an error if the parameters are
+/// invalid, see `validate_params`, or the obfuscation failed.
+#[cfg(feature = "rayon")]
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_batch_parallel(
+    values: &[u64],
+    sensitivity: f64,
+    epsilon: f64,
+    rounding_step: usize,
+    seed: u64,
+    chunk_size: usize,
+) -> Result<Vec<u64>, LaplaceError> {
+    use rand::SeedableRng;
+    use rayon::prelude::*;
+
+    validate_params(sensitivity, epsilon, rounding_step)?;
+    let chunks: Vec<Vec<u64>> = values
+        .par_chunks(chunk_size.max(1))
+        .enumerate()
+        .map(|(index, chunk)| {
+            let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(seed);
+            rng.set_stream(index as u64);
+            let mechanism = LaplaceMechanism::new(sensitivity, epsilon);
+            chunk
+                .iter()
+                .map(|&value| {
+                    let obfuscated_value = mechanism.perturb(exact_f64(value)?, &mut rng)?;
+                    round_parametric(obfuscated_value, rounding_step)
+                })
+                .collect()
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(chunks.into_iter().flatten().collect())
 }
 
-/// Draw a sample from a Laplace distribution.
+/// Derives the random generator of a bin from a master seed, as an independent ChaCha20
+/// stream per bin, so that the noise of a bin doesn't depend on the order in which, or the
+/// thread on which, the bins are obfuscated.
 ///
 /// # Arguments
 ///
-/// * `mu` - the mean of the distribution.
-/// * `b` - the scale parameter of the distribution, often equal to `sensitivity`/`epsilon`.
-/// /// * `rng` - random generator.
+/// * `master_seed` - Seed from which the random generators of all bins are derived.
+/// * `bin` - The bin that the random generator is derived for.
 ///
 /// # Returns
 ///
-/// Returns a random sample from the Laplace distribution with the given `mu` and `b`, or an error if the distribution creation failed.
-fn laplace(mu: f64, b: f64, rng: &mut rand::rngs::ThreadRng) -> Result<f64, LaplaceError> {
-    let dist =
-        Laplace::new(mu, b).map_err(|e| LaplaceError::DistributionCreationError(e))?;
-    Ok(dist.sample(rng))
-}
+/// The random generator of the bin, always the same for the same seed and bin.
+#[cfg(feature = "std")]
+pub fn rng_for_bin(master_seed: u64, bin: Bin) -> rand_chacha::ChaCha20Rng {
+    use rand::SeedableRng;
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(master_seed);
+    rng.set_stream(bin as u64);
+    rng
+}
 
-    #[test]
-    fn test_laplace_err() {
-        let mu = 10.0;
-        let b = 0.0;
-        let mut rng = rand::thread_rng();
-        let result = laplace(mu, b, &mut rng);
+/// Obfuscates each of the given values with `privatize` in parallel, using the random
+/// generator derived with `rng_for_bin` from `master_seed` and the bin of the value. Unlike
+/// `privatize_batch_parallel`, the result of each bin is the same regardless of the order of
+/// the values and the number of threads. The bins should be distinct, as values of the same
+/// bin receive the same noise.
+///
+/// # Arguments
+///
+/// * `values` - Clear values to permute.
+/// * `bins` - The bins that the values belong to, one per value.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * `rounding_step` - Rounding to the given number is performed.
+/// * `master_seed` - Seed from which the random generator of each bin is derived.
+///
+/// # Returns
+///
+/// The obfuscated values in the order of the input values, or an error if the slice lengths
+/// do not match or the obfuscation failed.
+#[cfg(feature = "rayon")]
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_bins_parallel(
+    values: &[u64],
+    bins: &[Bin],
+    sensitivity: f64,
+    epsilon: f64,
+    rounding_step: usize,
+    master_seed: u64,
+) -> Result<Vec<u64>, LaplaceError> {
+    use rayon::prelude::*;
+
+    if values.len() != bins.len() {
+        return Err(LaplaceError::LengthMismatch(values.len(), bins.len()));
+    }
+    validate_params(sensitivity, epsilon, rounding_step)?;
+    values
+        .par_iter()
+        .zip(bins)
+        .map(|(&value, &bin)| {
+            let mut rng = rng_for_bin(master_seed, bin);
+            privatize(value, sensitivity, epsilon, rounding_step, &mut rng)
+        })
+        .collect()
+}
+
+/// Performs the actual perturbation of a value with the (epsilon, 0) laplacian
+/// mechanism and rounds the result to the nearest step position. With an infinite epsilon,
+/// no noise is added, which disables privacy, e.g. for debugging pipelines end-to-end.
+/// Values above `MAX_EXACT_VALUE` are rejected, as they can't be perturbed exactly.
+///
+/// # Arguments
+///
+/// * `value` - Clear value to permute.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * `rounding_step` - Rounding to the given number is performed.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated value , or an error if the obfuscation failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize<R: Rng + ?Sized>(
+    value: u64,
+    sensitivity: f64,
+    epsilon: f64,
+    rounding_step: usize,
+    rng: &mut R,
+) -> Result<u64, LaplaceError> {
+    validate_params(sensitivity, epsilon, rounding_step)?;
+    privatize_with_rounder(
+        value,
+        sensitivity,
+        epsilon,
+        |value| round_parametric(value, rounding_step),
+        rng,
+    )
+}
+
+/// Performs the perturbation of a value like `privatize`, with a ChaCha20 random generator
+/// seeded from the given seed, e.g. for golden-file tests. The same arguments, including the
+/// seed, always give the same result.
+///
+/// # Arguments
+///
+/// * `value` - Clear value to permute.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * `rounding_step` - Rounding to the given number is performed.
+/// * `seed` - Seed of the random generator.
+///
+/// # Returns
+///
+/// The obfuscated value, or an error if the obfuscation failed.
+#[cfg(feature = "std")]
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_with_seed(
+    value: u64,
+    sensitivity: f64,
+    epsilon: f64,
+    rounding_step: usize,
+    seed: u64,
+) -> Result<u64, LaplaceError> {
+    use rand::SeedableRng;
+
+    let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(seed);
+    privatize(value, sensitivity, epsilon, rounding_step, &mut rng)
+}
+
+/// Performs the perturbation of a value like `privatize` and suppresses the obfuscated value
+/// if it is below `k`, so that no released count is smaller than `k`. Since the obfuscated
+/// rather than the clear value is compared, the suppression doesn't affect the privacy
+/// guarantee.
+///
+/// # Arguments
+///
+/// * `value` - Clear value to permute.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * `rounding_step` - Rounding to the given number is performed.
+/// * `k` - The smallest obfuscated value that isn't suppressed.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated value, `None` if it was suppressed, or an error if the obfuscation failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_and_suppress<R: Rng + ?Sized>(
+    value: u64,
+    sensitivity: f64,
+    epsilon: f64,
+    rounding_step: usize,
+    k: u64,
+    rng: &mut R,
+) -> Result<Option<u64>, LaplaceError> {
+    let obfuscated = privatize(value, sensitivity, epsilon, rounding_step, rng)?;
+    Ok((obfuscated >= k).then_some(obfuscated))
+}
+
+/// Performs the perturbation of a value with the given mechanism and rounds the result to the
+/// nearest step position, e.g. to switch between `LaplaceMechanism` and `IdentityMechanism`
+/// without changing the call site.
+///
+/// # Arguments
+///
+/// * `value` - Clear value to permute.
+/// * `mechanism` - The mechanism perturbing the value.
+/// * `rounding_step` - Rounding to the given number is performed.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated value, or an error if the obfuscation failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_with_mechanism<M: Mechanism, R: Rng + ?Sized>(
+    value: u64,
+    mechanism: &M,
+    rounding_step: usize,
+    rng: &mut R,
+) -> Result<u64, LaplaceError> {
+    if rounding_step == 0 {
+        return Err(LaplaceError::InvalidArgRoundingStepZero);
+    }
+    let obfuscated_value = mechanism.perturb(exact_f64(value)?, rng)?;
+    round_parametric(obfuscated_value, rounding_step)
+}
+
+/// Performs the perturbation of a value with the (epsilon, 0) laplacian mechanism without
+/// rounding to a step. The noised value is truncated towards zero to an integer once, so
+/// unlike `privatize` with a rounding step of 1, which rounds to the nearest integer, e.g.
+/// 10.7 becomes 10 rather than 11. Negative noised values become 0.
+///
+/// # Arguments
+///
+/// * `value` - Clear value to permute.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated value, or an error if the obfuscation failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_unrounded<R: Rng + ?Sized>(
+    value: u64,
+    sensitivity: f64,
+    epsilon: f64,
+    rng: &mut R,
+) -> Result<u64, LaplaceError> {
+    privatize_with_rounder(
+        value,
+        sensitivity,
+        epsilon,
+        |value| Ok(steps_to_u64(value.trunc(), 1)),
+        rng,
+    )
+}
+
+/// Performs the perturbation of a value with the (epsilon, 0) laplacian mechanism and
+/// rounds the result with the given rounder, e.g. to a step depending on the magnitude of
+/// the value. `privatize` uses `round_parametric` with a fixed step as the rounder.
+///
+/// # Arguments
+///
+/// * `value` - Clear value to permute.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * `rounder` - Rounds the unrounded obfuscated value.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated value, or an error if the obfuscation or the rounding failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_with_rounder<F, R>(
+    value: u64,
+    sensitivity: f64,
+    epsilon: f64,
+    rounder: F,
+    rng: &mut R,
+) -> Result<u64, LaplaceError>
+where
+    F: Fn(f64) -> Result<u64, LaplaceError>,
+    R: Rng + ?Sized,
+{
+    let obfuscated_value = privatize_f64(exact_f64(value)?, sensitivity, epsilon, rng)?;
+    rounder(obfuscated_value)
+}
+
+macro_rules! privatize_uint {
+    ($name:ident, $uint:ty) => {
+        #[doc = concat!("Performs the perturbation of a `", stringify!($uint), "` value like")]
+        #[doc = concat!("`privatize`. Obfuscated values exceeding `", stringify!($uint), "::MAX`")]
+        /// are clamped to the largest multiple of the rounding step that fits.
+        ///
+        /// # Arguments
+        ///
+        /// * `value` - Clear value to permute.
+        /// * `sensitivity` - Sensitivity of query.
+        /// * `epsilon` - Privacy budget parameter.
+        /// * `rounding_step` - Rounding to the given number is performed.
+        /// * rng - A secure random generator for seeded randomness.
+        ///
+        /// # Returns
+        ///
+        /// The obfuscated value, or an error if the obfuscation failed.
+        #[must_use = "the obfuscation may have failed"]
+        pub fn $name<R: Rng + ?Sized>(
+            value: $uint,
+            sensitivity: f64,
+            epsilon: f64,
+            rounding_step: usize,
+            rng: &mut R,
+        ) -> Result<$uint, LaplaceError> {
+            let value = u64::from(value);
+            let obfuscated_value = privatize(value, sensitivity, epsilon, rounding_step, rng)?;
+            let max = <$uint>::MAX as u64 / rounding_step as u64 * rounding_step as u64;
+            Ok(obfuscated_value.min(max) as $uint)
+        }
+    };
+}
+
+privatize_uint!(privatize_u32, u32);
+privatize_uint!(privatize_u16, u16);
+
+/// Performs the perturbation of a value with the (epsilon, 0) laplacian mechanism like
+/// `privatize`, and additionally returns the scale `b` of the noise, e.g. to record the
+/// calibration of each release for auditing. Unlike the noise, the scale may be published.
+///
+/// # Arguments
+///
+/// * `value` - Clear value to permute.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * `rounding_step` - Rounding to the given number is performed.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated value and the scale `sensitivity / epsilon`, 0 with an infinite epsilon, or
+/// an error if the obfuscation failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_checked<R: Rng + ?Sized>(
+    value: u64,
+    sensitivity: f64,
+    epsilon: f64,
+    rounding_step: usize,
+    rng: &mut R,
+) -> Result<(u64, f64), LaplaceError> {
+    validate_params(sensitivity, epsilon, rounding_step)?;
+    let mechanism = LaplaceMechanism::new(sensitivity, epsilon);
+    let scale = mechanism.scale()?;
+    let obfuscated_value = mechanism.perturb(exact_f64(value)?, rng)?;
+    Ok((round_parametric(obfuscated_value, rounding_step)?, scale))
+}
+
+/// Performs the perturbation of a value with the (epsilon, 0) laplacian mechanism like
+/// `privatize`, and additionally returns the noise that was added, e.g. for private audit
+/// logs. The noise must not be published, as it reveals the clear value.
+///
+/// # Arguments
+///
+/// * `value` - Clear value to permute.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * `rounding_step` - Rounding to the given number is performed.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated value and the unrounded Laplace noise, or an error if the obfuscation
+/// failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_with_noise<R: Rng + ?Sized>(
+    value: u64,
+    sensitivity: f64,
+    epsilon: f64,
+    rounding_step: usize,
+    rng: &mut R,
+) -> Result<(u64, f64), LaplaceError> {
+    let noise = LaplaceMechanism::new(sensitivity, epsilon).perturb(0.0, rng)?;
+    let obfuscated_value = privatize_with_injected_noise(value, noise, rounding_step)?;
+    Ok((obfuscated_value, noise))
+}
+
+/// Adds the given noise to a value and rounds the result to the nearest step position,
+/// without sampling, e.g. to test downstream logic with chosen noise values.
+///
+/// # Arguments
+///
+/// * `value` - Clear value to permute.
+/// * `noise` - The noise to add to the value.
+/// * `rounding_step` - Rounding to the given number is performed.
+///
+/// # Returns
+///
+/// The obfuscated value, or an error if the rounding failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_with_injected_noise(
+    value: u64,
+    noise: f64,
+    rounding_step: usize,
+) -> Result<u64, LaplaceError> {
+    round_parametric(exact_f64(value)? + noise, rounding_step)
+}
+
+/// Performs the perturbation of a value with the (epsilon, 0) laplacian mechanism,
+/// clamps negative perturbed values to zero and rounds the result to the nearest step
+/// position. Unlike `privatize`, small counts with large noise can never wrap around to
+/// huge values.
+///
+/// # Arguments
+///
+/// * `value` - Clear value to permute.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * `rounding_step` - Rounding to the given number is performed.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The non-negative obfuscated value, or an error if the obfuscation failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_clamped<R: Rng + ?Sized>(
+    value: u64,
+    sensitivity: f64,
+    epsilon: f64,
+    rounding_step: usize,
+    rng: &mut R,
+) -> Result<u64, LaplaceError> {
+    let obfuscated_value = privatize_f64(exact_f64(value)?, sensitivity, epsilon, rng)?;
+    round_parametric(obfuscated_value.max(0.0), rounding_step)
+}
+
+/// Performs the perturbation of a value with the (epsilon, 0) laplacian mechanism for a
+/// count known to be bounded, e.g. by the size of a cohort. The perturbed value is clamped
+/// to `[0, domain_max]` before rounding, and the rounded value is clamped to the largest
+/// multiple of the rounding step not exceeding `domain_max`, so the output never leaves the
+/// domain. Clamping is a post-processing step and doesn't affect the privacy guarantee.
+///
+/// # Arguments
+///
+/// * `value` - Clear value to permute.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * `domain_max` - The largest value of the domain, must be positive.
+/// * `rounding_step` - Rounding to the given number is performed.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated value within `[0, domain_max]`, or an error if the obfuscation failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_truncated<R: Rng + ?Sized>(
+    value: u64,
+    sensitivity: f64,
+    epsilon: f64,
+    domain_max: u64,
+    rounding_step: usize,
+    rng: &mut R,
+) -> Result<u64, LaplaceError> {
+    if domain_max == 0 {
+        return Err(LaplaceError::InvalidDomain);
+    }
+    let obfuscated_value = privatize_f64(exact_f64(value)?, sensitivity, epsilon, rng)?;
+    round_truncated(obfuscated_value, domain_max, rounding_step)
+}
+
+// rounds the obfuscated value clamped to `[0, domain_max]`, without exceeding `domain_max`
+fn round_truncated(
+    obfuscated_value: f64,
+    domain_max: u64,
+    rounding_step: usize,
+) -> Result<u64, LaplaceError> {
+    let rounded = round_parametric(
+        obfuscated_value.clamp(0.0, domain_max as f64),
+        rounding_step,
+    )?;
+    Ok(rounded.min(domain_max / rounding_step as u64 * rounding_step as u64))
+}
+
+/// Performs the perturbation of a value with noise sampled from the given distribution, e.g.
+/// a custom noise shape, and rounds the result to the nearest step position like `privatize`.
+/// The privacy guarantee depends entirely on the distribution, which this function can't
+/// check.
+///
+/// # Arguments
+///
+/// * `value` - Clear value to permute.
+/// * `dist` - The distribution the noise is sampled from.
+/// * `rounding_step` - Rounding to the given number is performed.
+/// * `domain_max` - If given, the perturbed value is clamped to `[0, domain_max]` like in
+///   `privatize_truncated`, must be positive.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated value, or an error if the domain is 0 or the rounding failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_with_distribution<D: Distribution<f64>, R: Rng + ?Sized>(
+    value: u64,
+    dist: &D,
+    rounding_step: usize,
+    domain_max: Option<u64>,
+    rng: &mut R,
+) -> Result<u64, LaplaceError> {
+    if rounding_step == 0 {
+        return Err(LaplaceError::InvalidArgRoundingStepZero);
+    }
+    let obfuscated_value = exact_f64(value)? + dist.sample(rng);
+    match domain_max {
+        None => round_parametric(obfuscated_value, rounding_step),
+        Some(0) => Err(LaplaceError::InvalidDomain),
+        Some(domain_max) => {
+            let rounded = round_parametric(
+                obfuscated_value.clamp(0.0, domain_max as f64),
+                rounding_step,
+            )?;
+            Ok(rounded.min(domain_max / rounding_step as u64 * rounding_step as u64))
+        }
+    }
+}
+
+/// Performs the perturbation of a value with the (epsilon, 0) laplacian mechanism
+/// without rounding the result, e.g. for obfuscating rates or proportions.
+///
+/// # Arguments
+///
+/// * `value` - Clear value to permute.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The unrounded obfuscated value, or an error if the obfuscation failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_f64<R: Rng + ?Sized>(
+    value: f64,
+    sensitivity: f64,
+    epsilon: f64,
+    rng: &mut R,
+) -> Result<f64, LaplaceError> {
+    LaplaceMechanism::new(sensitivity, epsilon).perturb(value, rng)
+}
+
+/// Obfuscates a proportion by perturbing the numerator count with the (epsilon, 0)
+/// laplacian mechanism with sensitivity 1 and dividing by the clear denominator. The
+/// result is clamped to `[0, 1]`.
+///
+/// # Arguments
+///
+/// * `numerator` - Clear count of the proportion to permute.
+/// * `denominator` - Total count the proportion is relative to.
+/// * `epsilon` - Privacy budget parameter.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated proportion, or an error if the denominator is zero, the numerator or the
+/// denominator exceed `MAX_EXACT_VALUE`, or the obfuscation failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_rate<R: Rng + ?Sized>(
+    numerator: u64,
+    denominator: u64,
+    epsilon: f64,
+    rng: &mut R,
+) -> Result<f64, LaplaceError> {
+    if denominator == 0 {
+        return Err(LaplaceError::ZeroDenominator);
+    }
+    let denominator = exact_f64(denominator)?;
+    let obfuscated_numerator = privatize_f64(exact_f64(numerator)?, 1.0, epsilon, rng)?;
+    Ok((obfuscated_numerator / denominator).clamp(0.0, 1.0))
+}
+
+/// Obfuscates the sum of per-record contributions with the (epsilon, 0) laplacian mechanism.
+/// Each contribution is clipped to `[-clip, clip]` before summing, so that the sum has
+/// sensitivity `clip` regardless of the clear values.
+///
+/// # Arguments
+///
+/// * `values` - Clear contributions of the records.
+/// * `clip` - Bound of the absolute value of each contribution.
+/// * `epsilon` - Privacy budget parameter.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The unrounded obfuscated sum, or an error if a contribution is NaN or the obfuscation
+/// failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_sum<R: Rng + ?Sized>(
+    values: &[f64],
+    clip: f64,
+    epsilon: f64,
+    rng: &mut R,
+) -> Result<f64, LaplaceError> {
+    validate_privacy_parameters(clip, epsilon)?;
+    if let Some(&value) = values.iter().find(|value| value.is_nan()) {
+        return Err(LaplaceError::NonFiniteParameter {
+            name: "value",
+            value,
+        });
+    }
+    let sum: f64 = values.iter().map(|value| value.clamp(-clip, clip)).sum();
+    privatize_f64(sum, clip, epsilon, rng)
+}
+
+/// Obfuscates the mean of per-record contributions by dividing an obfuscated sum by an
+/// obfuscated count. The privacy budget is split in half: `epsilon / 2` for the sum of the
+/// contributions clipped to `[-clip, clip]` as in `privatize_sum`, and `epsilon / 2` for the
+/// count with sensitivity 1. The obfuscated count is clamped to at least 1, and the mean to
+/// `[-clip, clip]`.
+///
+/// # Arguments
+///
+/// * `values` - Clear contributions of the records.
+/// * `clip` - Bound of the absolute value of each contribution.
+/// * `epsilon` - Privacy budget parameter, split between the sum and the count.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated mean, or an error if a contribution is NaN or the obfuscation failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_mean<R: Rng + ?Sized>(
+    values: &[f64],
+    clip: f64,
+    epsilon: f64,
+    rng: &mut R,
+) -> Result<f64, LaplaceError> {
+    let sum = privatize_sum(values, clip, epsilon / 2.0, rng)?;
+    let count = privatize_f64(values.len() as f64, 1.0, epsilon / 2.0, rng)?;
+    Ok((sum / count.max(1.0)).clamp(-clip, clip))
+}
+
+/// The maximum number of samples drawn by `privatize_bounded` before giving up.
+pub const MAX_RESAMPLING_ATTEMPTS: usize = 1000;
+
+/// Performs the perturbation of a value with the bounded laplacian mechanism, resampling the
+/// noise until the perturbed value lies within `[lower, upper]`. Unlike clamping, this
+/// doesn't pile up probability mass at the bounds.
+///
+/// Resampling renormalizes the density over the bounds, and the normalizing constants of
+/// neighbouring values differ by up to another factor `exp(epsilon)`, so noise of scale
+/// `sensitivity / epsilon` would only give 2 * epsilon-differential privacy. The noise is
+/// therefore sampled with scale `2 * sensitivity / epsilon`, which gives epsilon-differential
+/// privacy.
+///
+/// # Arguments
+///
+/// * `value` - Clear value to permute.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * `lower` - The lower bound of the perturbed value.
+/// * `upper` - The upper bound of the perturbed value.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The unrounded perturbed value within the bounds, or an error if the bounds are invalid,
+/// no sample was within the bounds after `MAX_RESAMPLING_ATTEMPTS` attempts, or the
+/// perturbation failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_bounded<R: Rng + ?Sized>(
+    value: f64,
+    sensitivity: f64,
+    epsilon: f64,
+    lower: f64,
+    upper: f64,
+    rng: &mut R,
+) -> Result<f64, LaplaceError> {
+    if !(lower < upper && lower.is_finite() && upper.is_finite()) {
+        return Err(LaplaceError::InvalidOutputBounds(lower, upper));
+    }
+    validate_privacy_parameters(sensitivity, epsilon)?;
+    let mechanism = LaplaceMechanism::new(2.0 * sensitivity, epsilon);
+    for _ in 0..MAX_RESAMPLING_ATTEMPTS {
+        let perturbed = mechanism.perturb(value, rng)?;
+        if (lower..=upper).contains(&perturbed) {
+            return Ok(perturbed);
+        }
+    }
+    Err(LaplaceError::ResamplingExhausted(MAX_RESAMPLING_ATTEMPTS))
+}
+
+/// Computes the scale `b` of the Laplace distribution the noise of the (epsilon, 0)
+/// laplacian mechanism is sampled from.
+///
+/// # Arguments
+///
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+///
+/// # Returns
+///
+/// The scale `sensitivity / epsilon`, or an error if the sensitivity or epsilon are invalid.
+#[must_use = "the obfuscation may have failed"]
+pub fn laplace_scale(sensitivity: f64, epsilon: f64) -> Result<f64, LaplaceError> {
+    validate_privacy_parameters(sensitivity, epsilon)?;
+    Ok(sensitivity / epsilon)
+}
+
+/// Computes the scale `b` of the noise implied by the given privacy budget parameter, like
+/// `laplace_scale`, but also checks that the scale can be sampled from.
+///
+/// # Arguments
+///
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+///
+/// # Returns
+///
+/// The scale `sensitivity / epsilon`, or an error if the sensitivity, epsilon or the scale are
+/// invalid.
+#[must_use = "the conversion may have failed"]
+pub fn scale_from_epsilon(sensitivity: f64, epsilon: f64) -> Result<f64, LaplaceError> {
+    let b = laplace_scale(sensitivity, epsilon)?;
+    validate_scale(b)?;
+    Ok(b)
+}
+
+/// Computes the privacy budget parameter implied by the given scale `b` of the noise, the
+/// inverse of `scale_from_epsilon`, e.g. to reason in terms of the magnitude of the noise.
+///
+/// # Arguments
+///
+/// * `sensitivity` - Sensitivity of query.
+/// * `b` - The scale of the Laplace distribution the noise is sampled from.
+///
+/// # Returns
+///
+/// The privacy budget parameter `sensitivity / b`, or an error if the sensitivity, the scale
+/// or epsilon are invalid.
+#[must_use = "the conversion may have failed"]
+pub fn epsilon_from_scale(sensitivity: f64, b: f64) -> Result<f64, LaplaceError> {
+    validate_scale(b)?;
+    let epsilon = sensitivity / b;
+    validate_privacy_parameters(sensitivity, epsilon)?;
+    Ok(epsilon)
+}
+
+/// Checks the parameters of `privatize` up front, e.g. to fail fast before obfuscating a large
+/// batch instead of midway through it. An infinite epsilon is valid and disables the noise.
+///
+/// # Arguments
+///
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * `rounding_step` - Rounding to the given number is performed.
+///
+/// # Returns
+///
+/// Returns an error if `privatize` would fail with the given parameters.
+pub fn validate_params(
+    sensitivity: f64,
+    epsilon: f64,
+    rounding_step: usize,
+) -> Result<(), LaplaceError> {
+    if !(epsilon == f64::INFINITY && sensitivity >= 0.0 && sensitivity.is_finite()) {
+        validate_scale(laplace_scale(sensitivity, epsilon)?)?;
+    }
+    if rounding_step == 0 {
+        return Err(LaplaceError::InvalidArgRoundingStepZero);
+    }
+    Ok(())
+}
+
+/// Performs the actual perturbation of a value with the (epsilon, delta) Gaussian
+/// mechanism and rounds the result to the nearest step position.
+///
+/// # Arguments
+///
+/// * `value` - Clear value to permute.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter, in the open interval (0, 1).
+/// * `delta` - Probability of the privacy guarantee failing, in the open interval (0, 1).
+/// * `rounding_step` - Rounding to the given number is performed.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated value, or an error if the obfuscation failed.
+#[cfg(feature = "std")]
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_gaussian<R: Rng + ?Sized>(
+    value: u64,
+    sensitivity: f64,
+    epsilon: f64,
+    delta: f64,
+    rounding_step: usize,
+    rng: &mut R,
+) -> Result<u64, LaplaceError> {
+    let obfuscated_value =
+        GaussianMechanism::new(sensitivity, epsilon, delta)?.perturb(exact_f64(value)?, rng)?;
+    round_parametric(obfuscated_value, rounding_step)
+}
+
+/// Performs the perturbation of a value with the snapping mechanism (Mironov, "On
+/// Significance of the Least Significant Bits For Differential Privacy", CCS 2012), which
+/// defends against attacks exploiting the irregular spacing of floating-point numbers.
+///
+/// The value is clamped to `[-bound, bound]`, perturbed with Laplace noise of scale
+/// `lambda`, snapped to the nearest multiple of the smallest power of two greater than or
+/// equal to `lambda`, and clamped to `[-bound, bound]` again.
+///
+/// Mironov shows that the mechanism with scale `lambda` is only
+/// `(sensitivity + 2^-49 * bound) / lambda`-differentially private, as the rounding of the
+/// floating-point noise costs some budget. The scale is therefore computed as
+/// `lambda = (sensitivity + 2^-49 * bound) / epsilon`, slightly larger than
+/// `sensitivity / epsilon`, so that the mechanism is epsilon-differentially private. The
+/// proof assumes `lambda < bound < 2^46 * lambda`, which is checked, and a uniform sample with
+/// the full 52 bits of precision, which is drawn by constructing its mantissa from random
+/// bits.
+///
+/// # Arguments
+///
+/// * `value` - Clear value to permute.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * `bound` - Bound on the absolute value of the input and output.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated value, or an error if the value is not finite, the bound doesn't satisfy
+/// the assumption of the proof, or the obfuscation failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_snapping<R: Rng + ?Sized>(
+    value: f64,
+    sensitivity: f64,
+    epsilon: f64,
+    bound: f64,
+    rng: &mut R,
+) -> Result<f64, LaplaceError> {
+    if !(bound > 0.0 && bound.is_finite()) {
+        return Err(LaplaceError::InvalidBound(bound));
+    }
+    if !value.is_finite() {
+        return Err(LaplaceError::NonFiniteParameter {
+            name: "value",
+            value,
+        });
+    }
+    validate_scale(laplace_scale(sensitivity, epsilon)?)?;
+    let lambda = (sensitivity + bound * 2f64.powi(-49)) / epsilon;
+    validate_scale(lambda)?;
+    if !(lambda < bound && bound < 2f64.powi(46) * lambda) {
+        return Err(LaplaceError::InvalidBound(bound));
+    }
+    // The noise is sampled as `S * lambda * ln(U)` with a uniform sign `S` and a uniform
+    // `U` in (0, 1], which is Laplace distributed with scale `lambda`.
+    let uniform = uniform_open_closed_52(rng);
+    let sign = if rng.gen::<bool>() { 1.0 } else { -1.0 };
+    let noisy = value.clamp(-bound, bound) + sign * lambda * uniform.ln();
+    // Snapping to the power-of-two grid collapses the floating-point neighbourhood of the
+    // noisy value, so its least significant bits no longer reveal the clear value.
+    let granularity = snapping_granularity(lambda);
+    let snapped = (noisy / granularity).round() * granularity;
+    Ok(snapped.clamp(-bound, bound))
+}
+
+/// Performs the perturbation of a signed value with the (epsilon, 0) laplacian mechanism
+/// and rounds the result to the nearest step position, preserving the sign. Values whose
+/// magnitude exceeds `MAX_EXACT_VALUE` are rejected, as they can't be perturbed exactly, and
+/// rounded values beyond the range of `i64` saturate to the largest representable multiple
+/// of the rounding step.
+///
+/// # Arguments
+///
+/// * `value` - Clear value to permute, e.g. a difference of two counts.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * `rounding_step` - Rounding to the given number is performed.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated signed value, or an error if the obfuscation failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_i64<R: Rng + ?Sized>(
+    value: i64,
+    sensitivity: f64,
+    epsilon: f64,
+    rounding_step: usize,
+    rng: &mut R,
+) -> Result<i64, LaplaceError> {
+    if value.unsigned_abs() > MAX_EXACT_VALUE {
+        return Err(LaplaceError::PrecisionLoss(value.unsigned_abs()));
+    }
+    let obfuscated_value = privatize_f64(value as f64, sensitivity, epsilon, rng)?;
+    round_parametric_i64(obfuscated_value, rounding_step)
+}
+
+/// Performs the perturbation of an integer value with the discrete Laplace (two-sided
+/// geometric) mechanism. The noise is integer-valued, so no rounding step is needed.
+///
+/// # Arguments
+///
+/// * `value` - Clear value to permute.
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated value, or an error if the obfuscation failed.
+#[cfg(feature = "std")]
+#[must_use = "the obfuscation may have failed"]
+pub fn privatize_discrete<R: Rng + ?Sized>(
+    value: i64,
+    sensitivity: u64,
+    epsilon: f64,
+    rng: &mut R,
+) -> Result<i64, LaplaceError> {
+    validate_privacy_parameters(sensitivity as f64, epsilon)?;
+    Ok(value.saturating_add(discrete_laplace(sensitivity, epsilon, rng)?))
+}
+
+/// Rounds the value to the nearest multiple of the step parameter.
+///
+/// # Arguments
+///
+/// * `value` - The value to be rounded.
+/// * `stepParameter` - The step to round to, for example, 1, 5, or 10.
+///
+/// # Returns
+///
+/// Returns the rounded value, or an error if the rounding failed, e.g. because the value is
+/// NaN or infinite and likely the result of invalid noise.
+#[must_use = "the obfuscation may have failed"]
+fn round_parametric(value: f64, step_parameter: usize) -> Result<u64, LaplaceError> {
+    if step_parameter == 0 {
+        return Err(LaplaceError::InvalidArgRoundingStepZero);
+    }
+    if !value.is_finite() {
+        return Err(LaplaceError::NonFiniteParameter {
+            name: "value",
+            value,
+        });
+    }
+    let steps = (value / step_parameter as f64).round();
+    Ok(steps_to_u64(steps, step_parameter))
+}
+
+/// Rounds the value to a multiple of the step parameter in the given rounding mode.
+///
+/// # Arguments
+///
+/// * `value` - The value to be rounded.
+/// * `step_parameter` - The step to round to, for example, 1, 5, or 10.
+/// * `mode` - Whether to round to the nearest, the lower, the upper or a random multiple.
+/// * `rng` - A secure random generator, only used for stochastic rounding.
+///
+/// # Returns
+///
+/// Returns the rounded value, or an error if the rounding failed.
+#[must_use = "the obfuscation may have failed"]
+pub fn round_parametric_with_mode<R: Rng + ?Sized>(
+    value: f64,
+    step_parameter: usize,
+    mode: RoundingMode,
+    rng: &mut R,
+) -> Result<u64, LaplaceError> {
+    if step_parameter == 0 {
+        return Err(LaplaceError::InvalidArgRoundingStepZero);
+    }
+    if !value.is_finite() {
+        return Err(LaplaceError::NonFiniteParameter {
+            name: "value",
+            value,
+        });
+    }
+    let steps = value / step_parameter as f64;
+    let rounded_steps = match mode {
+        RoundingMode::Nearest => steps.round(),
+        RoundingMode::Floor => steps.floor(),
+        RoundingMode::Ceil => steps.ceil(),
+        RoundingMode::Stochastic => {
+            let lower = steps.floor();
+            if rng.gen::<f64>() < steps - lower {
+                lower + 1.0
+            } else {
+                lower
+            }
+        }
+    };
+    Ok(steps_to_u64(rounded_steps, step_parameter))
+}
+
+/// Converts a rounded number of steps to the multiple of the step parameter, clamped to the
+/// multiples representable as `u64`. Negative values become 0, values too large for `u64`
+/// become the largest multiple of the step parameter.
+fn steps_to_u64(rounded_steps: f64, step_parameter: usize) -> u64 {
+    let step = step_parameter as u64;
+    let max_steps = u64::MAX / step;
+    if rounded_steps.is_nan() || rounded_steps <= 0.0 {
+        0
+    } else if rounded_steps >= max_steps as f64 {
+        max_steps * step
+    } else {
+        rounded_steps as u64 * step
+    }
+}
+
+/// Rounds the signed value to the nearest multiple of the step parameter.
+///
+/// # Arguments
+///
+/// * `value` - The value to be rounded, may be negative.
+/// * `step_parameter` - The step to round to, for example, 1, 5, or 10.
+///
+/// # Returns
+///
+/// Returns the rounded value, or an error if the rounding failed.
+#[must_use = "the obfuscation may have failed"]
+fn round_parametric_i64(value: f64, step_parameter: usize) -> Result<i64, LaplaceError> {
+    if step_parameter == 0 {
+        return Err(LaplaceError::InvalidArgRoundingStepZero);
+    }
+    if !value.is_finite() {
+        return Err(LaplaceError::NonFiniteParameter {
+            name: "value",
+            value,
+        });
+    }
+    let step = i64::try_from(step_parameter).map_err(|_| {
+        LaplaceError::RoundingStepError(format!(
+            "Rounding step {step_parameter} exceeds the largest i64"
+        ))
+    })?;
+    // the cast saturates, and clamping the steps keeps their multiple representable as `i64`
+    let max_steps = i64::MAX / step;
+    let steps = ((value / step as f64).round() as i64).clamp(-max_steps, max_steps);
+    Ok(steps * step)
+}
+
+/// Draws a uniform sample from (0, 1] on the grid of multiples of 2^-52, by filling the
+/// mantissa of a number in [1, 2) with 52 random bits and subtracting it from 2, which is
+/// exact.
+fn uniform_open_closed_52<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+    let mantissa = rng.next_u64() >> 12;
+    2.0 - f64::from_bits(1f64.to_bits() | mantissa)
+}
+
+/// Returns the snapping interval of the snapping mechanism, the smallest power of two that
+/// is greater than or equal to the given Laplace scale.
+fn snapping_granularity(lambda: f64) -> f64 {
+    2f64.powi(lambda.log2().ceil() as i32)
+}
+
+/// Draw a sample from a Laplace distribution.
+///
+/// # Arguments
+///
+/// * `mu` - the mean of the distribution.
+/// * `b` - the scale parameter of the distribution, often equal to `sensitivity`/`epsilon`.
+/// * `rng` - random generator.
+///
+/// # Returns
+///
+/// Returns a random sample from the Laplace distribution with the given `mu` and `b`, or an error if the distribution creation failed.
+#[must_use = "the obfuscation may have failed"]
+fn laplace<R: Rng + ?Sized>(mu: f64, b: f64, rng: &mut R) -> Result<f64, LaplaceError> {
+    if !mu.is_finite() {
+        return Err(LaplaceError::NonFiniteParameter {
+            name: "mu",
+            value: mu,
+        });
+    }
+    validate_scale(b)?;
+    Ok(sample_laplace(mu, b, rng))
+}
+
+/// Draw a sample from a Laplace distribution whose parameters are already validated.
+fn sample_laplace<R: Rng + ?Sized>(mu: f64, b: f64, rng: &mut R) -> f64 {
+    // inverse transform sampling as in `statrs::distribution::Laplace`, which isn't available
+    // without std
+    let x: f64 = rng.gen_range(-0.5..0.5);
+    mu - b * x.signum() * (1. - 2. * x.abs()).ln()
+}
+
+/// Draw a sample from a Laplace distribution whose parameters are already validated, by
+/// inverting the CDF at a uniform sample from the open interval (0, 1), so that the sample is
+/// finite even at the boundaries of the interval.
+fn sample_laplace_secure<R: Rng + ?Sized>(mu: f64, b: f64, rng: &mut R) -> f64 {
+    let u: f64 = rng.sample(Open01);
+    let x = u - 0.5;
+    mu - b * x.signum() * (1. - 2. * x.abs()).ln()
+}
+
+/// Draw `len` samples from a Laplace distribution centered at zero with the given, already
+/// validated scale, inverting the CDF at uniform samples from the open interval (0, 1) like
+/// `sample_laplace_secure`, with the random bits of all samples generated at once.
+#[cfg(feature = "bulk")]
+fn sample_laplace_bulk<R: Rng + ?Sized>(b: f64, len: usize, rng: &mut R) -> Vec<f64> {
+    let mut bits = vec![0u64; len];
+    rng.fill(&mut bits[..]);
+    bits.iter()
+        .map(|&bits| {
+            // the upper 52 bits as the midpoint of one of 2^52 equal subintervals of (0, 1)
+            let u = ((bits >> 12) as f64 + 0.5) / (1u64 << 52) as f64;
+            let x = u - 0.5;
+            -b * x.signum() * (1. - 2. * x.abs()).ln()
+        })
+        .collect()
+}
+
+/// The largest value that can be obfuscated without losing precision, 2^53, since larger
+/// integers can't all be represented exactly as `f64`.
+pub const MAX_EXACT_VALUE: u64 = 1 << 53;
+
+/// Converts the value to `f64`, or returns an error if it exceeds `MAX_EXACT_VALUE` and would
+/// lose precision.
+pub(crate) fn exact_f64(value: u64) -> Result<f64, LaplaceError> {
+    if value > MAX_EXACT_VALUE {
+        return Err(LaplaceError::PrecisionLoss(value));
+    }
+    Ok(value as f64)
+}
+
+/// Suggests a rounding step for values up to `expected_max_value`, the largest step of the
+/// form 1, 2 or 5 times a power of 10 that is at most a hundredth of the value, so that the
+/// values are rounded to about 100 distinct steps or more.
+///
+/// # Arguments
+///
+/// * `expected_max_value` - The largest value expected to be obfuscated.
+///
+/// # Returns
+///
+/// The suggested rounding step, at least 1.
+pub fn suggest_rounding_step(expected_max_value: u64) -> usize {
+    let target = expected_max_value / 100;
+    let mut power: u64 = 1;
+    let mut step: u64 = 1;
+    while let Some(next_power) = power.checked_mul(10) {
+        for multiple in [1, 2, 5] {
+            if power * multiple <= target {
+                step = power * multiple;
+            }
+        }
+        if next_power > target {
+            break;
+        }
+        power = next_power;
+    }
+    usize::try_from(step).unwrap_or(usize::MAX)
+}
+
+/// Checks that the rounding step is small enough for values up to `expected_max_value`, e.g.
+/// to catch configuration mistakes that would round almost all values to 0. The step is too
+/// large if it exceeds half the expected maximum value, since the values would then be
+/// rounded to at most 3 distinct steps. See `suggest_rounding_step` for a suitable step.
+///
+/// # Arguments
+///
+/// * `rounding_step` - The granularity of the rounding.
+/// * `expected_max_value` - The largest value expected to be obfuscated.
+///
+/// # Returns
+///
+/// Returns an error if the rounding step is 0 or too large.
+pub fn check_rounding_step(
+    rounding_step: usize,
+    expected_max_value: u64,
+) -> Result<(), LaplaceError> {
+    if rounding_step == 0 {
+        return Err(LaplaceError::InvalidArgRoundingStepZero);
+    }
+    if (rounding_step as u64).saturating_mul(2) > expected_max_value.max(1) {
+        return Err(LaplaceError::StepTooLargeRelativeToValue {
+            rounding_step,
+            expected_max_value,
+        });
+    }
+    Ok(())
+}
+
+/// Checks that `epsilon` is positive and finite, and that `sensitivity` is non-negative and
+/// finite.
+pub(crate) fn validate_privacy_parameters(
+    sensitivity: f64,
+    epsilon: f64,
+) -> Result<(), LaplaceError> {
+    if !(epsilon > 0.0 && epsilon.is_finite()) {
+        return Err(LaplaceError::InvalidEpsilon(epsilon));
+    }
+    if !(sensitivity >= 0.0 && sensitivity.is_finite()) {
+        return Err(LaplaceError::InvalidSensitivity(sensitivity));
+    }
+    Ok(())
+}
+
+/// Checks that the scale parameter `b` of a Laplace distribution is finite and positive.
+fn validate_scale(b: f64) -> Result<(), LaplaceError> {
+    if !b.is_finite() {
+        return Err(LaplaceError::NonFiniteParameter {
+            name: "b",
+            value: b,
+        });
+    }
+    if b <= 0.0 {
+        return Err(LaplaceError::NonPositiveScale { b });
+    }
+    Ok(())
+}
+
+/// Draw a sample from a discrete Laplace (two-sided geometric) distribution centered at zero.
+///
+/// # Arguments
+///
+/// * `sensitivity` - Sensitivity of query.
+/// * `epsilon` - Privacy budget parameter.
+/// * `rng` - random generator.
+///
+/// # Returns
+///
+/// Returns the difference of two samples from a geometric distribution with parameter
+/// `1 - exp(-epsilon/sensitivity)`, or an error if the distribution creation failed.
+#[cfg(feature = "std")]
+#[must_use = "the obfuscation may have failed"]
+fn discrete_laplace<R: Rng + ?Sized>(
+    sensitivity: u64,
+    epsilon: f64,
+    rng: &mut R,
+) -> Result<i64, LaplaceError> {
+    let p = 1.0 - (-epsilon / sensitivity as f64).exp();
+    let dist = Geometric::new(p).map_err(LaplaceError::GeometricDistributionCreationError)?;
+    let positive: u64 = dist.sample(rng);
+    let negative: u64 = dist.sample(rng);
+    Ok(positive as i64 - negative as i64)
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn key(sensitivity: f64, epsilon: f64, count: Count, bin: Bin) -> CacheKey {
+        (sensitivity.to_bits(), epsilon.to_bits(), count, bin)
+    }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    // the types are embedded in shared application state, so a field that isn't `Send` or
+    // `Sync` must not be introduced by accident
+    #[test]
+    fn test_types_send_sync() {
+        assert_send_sync::<ObfCache>();
+        assert_send_sync::<sync_cache::SyncObfCache>();
+        assert_send_sync::<LaplaceError>();
+        assert_send_sync::<config::DiffPrivConfig>();
+        assert_send_sync::<budget::BudgetAccountant>();
+        assert_send_sync::<continual::ContinualCounter>();
+        assert_send_sync::<obfuscator::Obfuscator>();
+        assert_send_sync::<LaplaceMechanism>();
+        assert_send_sync::<GaussianMechanism>();
+        assert_send_sync::<mechanism::IdentityMechanism>();
+        assert_send_sync::<mechanism::PrivacyGuarantee>();
+        assert_send_sync::<MergePolicy>();
+        assert_send_sync::<ObfuscateBelow10Mode>();
+        assert_send_sync::<ObfuscationResult>();
+        assert_send_sync::<SmallCountPolicy>();
+        assert_send_sync::<SuppressionMode>();
+        assert_send_sync::<RoundingMode>();
+        assert_send_sync::<RoundingOrder>();
+    }
+
+    #[test]
+    fn test_laplace_err() {
+        let mu = 10.0;
+        let b = 0.0;
+        let mut rng = rand::thread_rng();
+        let result = laplace(mu, b, &mut rng);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_laplace_err_variants() {
+        let mut rng = rand::thread_rng();
+        assert!(matches!(
+            laplace(0.0, 0.0, &mut rng),
+            Err(LaplaceError::NonPositiveScale { b }) if b == 0.0
+        ));
+        assert!(matches!(
+            laplace(0.0, -1.0, &mut rng),
+            Err(LaplaceError::NonPositiveScale { b }) if b == -1.0
+        ));
+        assert!(matches!(
+            laplace(0.0, f64::NAN, &mut rng),
+            Err(LaplaceError::NonFiniteParameter { name: "b", .. })
+        ));
+        assert!(matches!(
+            laplace(f64::NAN, 1.0, &mut rng),
+            Err(LaplaceError::NonFiniteParameter { name: "mu", .. })
+        ));
+    }
+
+    #[test]
+    fn test_laplace_ok() {
+        let mu = 10.0;
+        let b = 1.0;
+        let mut rng = rand::thread_rng();
+        let result = laplace(mu, b, &mut rng);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_laplace_output_within_range() {
+        let mu = 10.0;
+        let b = 1.0;
+        let mut rng = rand::thread_rng();
+        let result = laplace(mu, b, &mut rng).unwrap();
+        assert!(result >= mu - 10.0 * b && result <= mu + 10.0 * b);
+    }
+
+    fn laplace_cdf(x: f64, mu: f64, b: f64) -> f64 {
+        if x < mu {
+            0.5 * ((x - mu) / b).exp()
+        } else {
+            1.0 - 0.5 * (-(x - mu) / b).exp()
+        }
+    }
+
+    #[test]
+    fn test_laplace_empirical_distribution() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let b = 2.0;
+        let n = 100_000;
+        let mut samples: Vec<f64> = (0..n).map(|_| laplace(0.0, b, &mut rng).unwrap()).collect();
+
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+        assert!(mean.abs() < 0.05, "mean {mean}");
+        let expected_variance = 2.0 * b * b;
+        assert!(
+            (variance / expected_variance - 1.0).abs() < 0.05,
+            "variance {variance}"
+        );
+
+        // Kolmogorov-Smirnov statistic against the Laplace CDF, with the critical value at the
+        // 0.1% significance level
+        samples.sort_by(f64::total_cmp);
+        let ks = samples
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| {
+                let cdf = laplace_cdf(x, 0.0, b);
+                (cdf - i as f64 / n as f64).max((i + 1) as f64 / n as f64 - cdf)
+            })
+            .fold(0.0, f64::max);
+        assert!(ks < 1.95 / (n as f64).sqrt(), "KS statistic {ks}");
+    }
+
+    #[test]
+    fn test_discrete_laplace_err() {
+        let mut rng = rand::thread_rng();
+        let result = discrete_laplace(1, 0.0, &mut rng);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_privatize_discrete_symmetric() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let value = 100;
+        let samples = 20_000;
+        let (mut above, mut below, mut sum) = (0, 0, 0i64);
+        for _ in 0..samples {
+            let result = privatize_discrete(value, 1, 0.5, &mut rng).unwrap();
+            sum += result;
+            if result > value {
+                above += 1;
+            } else if result < value {
+                below += 1;
+            }
+        }
+        let mean = sum as f64 / samples as f64;
+        assert!((mean - value as f64).abs() < 0.1);
+        assert!((above as f64 - below as f64).abs() < 0.05 * samples as f64);
+        assert!(above > 0 && below > 0);
+    }
+
+    #[test]
+    fn test_privatize_checked_scale() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut rng_privatize = StdRng::seed_from_u64(42);
+        for (sensitivity, epsilon) in [(1.0, 0.1), (10.0, 0.5), (2.0, 3.0)] {
+            let (obfuscated, scale) =
+                privatize_checked(27, sensitivity, epsilon, 5, &mut rng).unwrap();
+            assert_eq!(scale, sensitivity / epsilon);
+            assert_eq!(
+                obfuscated,
+                privatize(27, sensitivity, epsilon, 5, &mut rng_privatize).unwrap()
+            );
+        }
+        assert_eq!(
+            privatize_checked(27, 1.0, f64::INFINITY, 1, &mut rng).unwrap(),
+            (27, 0.0)
+        );
+        assert!(privatize_checked(27, 1.0, 0.0, 1, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_privatize_precision_boundary() {
+        let mut rng = rand::thread_rng();
+        let (obfuscated, _) = privatize_with_noise(MAX_EXACT_VALUE, 1.0, 1.0, 1, &mut rng).unwrap();
+        assert!(obfuscated.abs_diff(MAX_EXACT_VALUE) < 100);
+        assert_eq!(
+            privatize(MAX_EXACT_VALUE, 1.0, f64::INFINITY, 1, &mut rng).unwrap(),
+            MAX_EXACT_VALUE
+        );
+        assert_eq!(
+            privatize_with_injected_noise(MAX_EXACT_VALUE - 1, 1.0, 1).unwrap(),
+            MAX_EXACT_VALUE
+        );
+        for value in [MAX_EXACT_VALUE + 1, MAX_EXACT_VALUE + 3, u64::MAX] {
+            assert!(matches!(
+                privatize(value, 1.0, 1.0, 1, &mut rng),
+                Err(LaplaceError::PrecisionLoss(v)) if v == value
+            ));
+            assert!(matches!(
+                privatize_with_injected_noise(value, 0.0, 1),
+                Err(LaplaceError::PrecisionLoss(_))
+            ));
+            assert!(matches!(
+                privatize_truncated(value, 1.0, 1.0, u64::MAX, 1, &mut rng),
+                Err(LaplaceError::PrecisionLoss(_))
+            ));
+            assert!(matches!(
+                privatize_clamped(value, 1.0, 1.0, 1, &mut rng),
+                Err(LaplaceError::PrecisionLoss(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_obf_cache_clone_independent() {
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::with_capacity(10).unwrap();
+        obf_cache.preload(1.0, 0.5, 27, 1, 30);
+        obf_cache.preload(1.0, 0.5, 35, 2, 40);
+
+        let mut cloned = obf_cache.clone();
+        assert_eq!(cloned.cache, obf_cache.cache);
+        assert_eq!(cloned.max_entries(), Some(10));
+        cloned.preload(1.0, 0.5, 27, 1, 20);
+        get_from_cache_or_privatize(
+            50,
+            1.0,
+            0.5,
+            3,
+            Some(&mut cloned),
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(cloned.len(), 3);
+        assert_eq!(obf_cache.len(), 2);
+        assert_eq!(obf_cache.peek(1.0, 0.5, 27, 1), Some(30));
+        assert_eq!(obf_cache.misses(), 0);
+    }
+
+    #[test]
+    fn test_obf_cache_debug_summary() {
+        let obf_cache = ObfCache::with_entries([((1.0, 0.5, 27, 1), 12345)]);
+        let debug = format!("{:?}", obf_cache);
+        assert_eq!(
+            debug,
+            "ObfCache { len: 1, max_entries: None, hits: 0, misses: 0 }"
+        );
+        assert_eq!(format!("{:?}", ObfuscateBelow10Mode::Ten), "Ten");
+    }
+
+    #[test]
+    fn test_obf_cache_approx_memory_bytes_linear() {
+        let filled = |mut obf_cache: ObfCache, len| {
+            for count in 0..len {
+                obf_cache.preload(1.0, 0.5, count, 1, count);
+            }
+            obf_cache.approx_memory_bytes()
+        };
+        let empty = ObfCache::new().approx_memory_bytes();
+        assert!(empty > 0);
+        let per_entry = filled(ObfCache::new(), 1) - empty;
+        assert!(per_entry >= core::mem::size_of::<(CacheKey, u64)>());
+        assert_eq!(filled(ObfCache::new(), 100), empty + 100 * per_entry);
+        assert_eq!(filled(ObfCache::new(), 1000), empty + 1000 * per_entry);
+
+        let bounded = || ObfCache::with_capacity(500).unwrap();
+        let bounded_per_entry = filled(bounded(), 1) - empty;
+        assert!(bounded_per_entry > per_entry);
+        assert_eq!(filled(bounded(), 100), empty + 100 * bounded_per_entry);
+        // evicted entries aren't counted
+        assert_eq!(filled(bounded(), 1000), empty + 500 * bounded_per_entry);
+    }
+
+    #[test]
+    fn test_obf_cache_to_manifest() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut obf_cache = ObfCache::new();
+        for (sensitivity, bin) in [(2.0, 3), (1.0, 7), (1.0, 2), (0.5, 2)] {
+            for value in [1000, 27, 500] {
+                get_from_cache_or_privatize(
+                    value,
+                    sensitivity,
+                    0.5,
+                    bin,
+                    Some(&mut obf_cache),
+                    true,
+                    ObfuscateBelow10Mode::Obfuscate,
+                    1,
+                    &mut rng,
+                )
+                .unwrap();
+            }
+        }
+        let manifest = obf_cache.to_manifest();
+        assert_eq!(manifest.len(), 12);
+        assert!(manifest.windows(2).all(|records| {
+            let key =
+                |record: &CacheRecord| (record.sensitivity.to_bits(), record.count, record.bin);
+            key(&records[0]) < key(&records[1])
+        }));
+        assert_eq!(manifest[0].sensitivity, 0.5);
+        assert_eq!((manifest[0].count, manifest[0].bin), (27, 2));
+        assert_eq!(obf_cache.peek(0.5, 0.5, 27, 2), Some(manifest[0].value));
+
+        let rebuilt = ObfCache::with_entries(manifest.iter().map(|record| {
+            (
+                (record.sensitivity, record.epsilon, record.count, record.bin),
+                record.value,
+            )
+        }));
+        assert_eq!(rebuilt.to_manifest(), manifest);
+        assert_eq!(obf_cache.clone().to_manifest(), manifest);
+    }
+
+    #[test]
+    fn test_obf_cache_max_bins() {
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::new().with_max_bins(100);
+        assert_eq!(obf_cache.max_bins(), Some(100));
+        let mut obfuscate = |bin, obf_cache: &mut ObfCache| {
+            get_from_cache_or_privatize(
+                27,
+                1.0,
+                0.5,
+                bin,
+                Some(obf_cache),
+                true,
+                ObfuscateBelow10Mode::Obfuscate,
+                1,
+                &mut rng,
+            )
+        };
+        assert!(obfuscate(0, &mut obf_cache).is_ok());
+        assert!(obfuscate(99, &mut obf_cache).is_ok());
+        assert!(matches!(
+            obfuscate(100, &mut obf_cache),
+            Err(LaplaceError::BinOutOfRange {
+                bin: 100,
+                max_bins: 100
+            })
+        ));
+        assert!(obfuscate(27_000, &mut obf_cache).is_err());
+        assert_eq!(obf_cache.len(), 2);
+
+        let mut unbounded = ObfCache::new();
+        assert_eq!(unbounded.max_bins(), None);
+        assert!(obfuscate(27_000, &mut unbounded).is_ok());
+    }
+
+    #[test]
+    fn test_obf_cache_merge() {
+        let merged = |policy| {
+            let mut obf_cache =
+                ObfCache::with_entries([((1.0, 0.5, 27, 1), 30), ((1.0, 0.5, 35, 1), 40)]);
+            let other = ObfCache::with_entries([((1.0, 0.5, 27, 1), 20), ((1.0, 0.5, 27, 2), 25)]);
+            obf_cache.merge(other, policy);
+            obf_cache
+        };
+
+        let obf_cache = merged(MergePolicy::KeepExisting);
+        assert_eq!(obf_cache.len(), 3);
+        assert_eq!(obf_cache.peek(1.0, 0.5, 27, 1), Some(30));
+        assert_eq!(obf_cache.peek(1.0, 0.5, 35, 1), Some(40));
+        assert_eq!(obf_cache.peek(1.0, 0.5, 27, 2), Some(25));
+
+        let obf_cache = merged(MergePolicy::Overwrite);
+        assert_eq!(obf_cache.len(), 3);
+        assert_eq!(obf_cache.peek(1.0, 0.5, 27, 1), Some(20));
+        assert_eq!(obf_cache.peek(1.0, 0.5, 35, 1), Some(40));
+        assert_eq!(obf_cache.peek(1.0, 0.5, 27, 2), Some(25));
+    }
+
+    #[test]
+    fn test_obf_cache_merge_stats_and_capacity() {
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::with_capacity(2).unwrap();
+        let mut other = ObfCache::new();
+        for value in [10, 20, 30] {
+            get_from_cache_or_privatize(
+                value,
+                1.0,
+                0.5,
+                1,
+                Some(&mut other),
+                true,
+                ObfuscateBelow10Mode::Obfuscate,
+                1,
+                &mut rng,
+            )
+            .unwrap();
+        }
+        get_from_cache_or_privatize(
+            10,
+            1.0,
+            0.5,
+            1,
+            Some(&mut other),
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        )
+        .unwrap();
+
+        obf_cache.merge(other, MergePolicy::Overwrite);
+        assert_eq!(obf_cache.len(), 2);
+        assert_eq!(obf_cache.hits(), 1);
+        assert_eq!(obf_cache.misses(), 3);
+    }
+
+    #[test]
+    fn test_obf_cache_preload() {
+        let mut rng = rand::rngs::mock::StepRng::new(0, 0);
+        let mut obf_cache =
+            ObfCache::with_entries([((1.0, 0.5, 27, 1), 12345), ((1.0, 0.5, 27, 2), 0)]);
+        obf_cache.preload(2.0, 0.5, 27, 1, 54321);
+        assert_eq!(obf_cache.len(), 3);
+        assert_eq!(obf_cache.peek(1.0, 0.5, 27, 2), Some(0));
+
+        let result = get_from_cache_or_privatize(
+            27,
+            1.0,
+            0.5,
+            1,
+            Some(&mut obf_cache),
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(result, 12345);
+        let result = get_from_cache_or_privatize(
+            27,
+            2.0,
+            0.5,
+            1,
+            Some(&mut obf_cache),
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(result, 54321);
+        assert_eq!(obf_cache.hits(), 2);
+        assert_eq!(obf_cache.misses(), 0);
+    }
+
+    #[test]
+    fn test_privatize_with_seed_deterministic() {
+        for value in [0, 27, 1000] {
+            let first = privatize_with_seed(value, 1.0, 0.1, 5, 42).unwrap();
+            assert_eq!(first, privatize_with_seed(value, 1.0, 0.1, 5, 42).unwrap());
+            let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(42);
+            assert_eq!(first, privatize(value, 1.0, 0.1, 5, &mut rng).unwrap());
+        }
+        let seeded = |seed| privatize_with_seed(1000, 1.0, 0.1, 1, seed).unwrap();
+        assert!((0..10).any(|seed| seeded(seed) != seeded(42)));
+        assert!(privatize_with_seed(27, 1.0, 0.0, 1, 42).is_err());
+    }
+
+    #[test]
+    fn test_privatize_and_suppress_threshold() {
+        let mut rng = rand::thread_rng();
+        let noiseless = |value| {
+            privatize_and_suppress(value, 1.0, f64::INFINITY, 1, 10, &mut rand::thread_rng())
+                .unwrap()
+        };
+        assert_eq!(noiseless(0), None);
+        assert_eq!(noiseless(9), None);
+        assert_eq!(noiseless(10), Some(10));
+        assert_eq!(noiseless(11), Some(11));
+        assert_eq!(
+            privatize_and_suppress(9, 1.0, f64::INFINITY, 5, 10, &mut rng).unwrap(),
+            Some(10)
+        );
+        assert!(privatize_and_suppress(10, 1.0, 1.0, 0, 10, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_privatize_with_mechanism_matches_privatize() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut rng_privatize = StdRng::seed_from_u64(42);
+        let mechanism = LaplaceMechanism::new(1.0, 0.5);
+        for value in [0, 27, 1000] {
+            assert_eq!(
+                privatize_with_mechanism(value, &mechanism, 5, &mut rng).unwrap(),
+                privatize(value, 1.0, 0.5, 5, &mut rng_privatize).unwrap()
+            );
+        }
+        let result = privatize_with_mechanism(27, &mechanism, 0, &mut rng);
+        assert!(matches!(
+            result,
+            Err(LaplaceError::InvalidArgRoundingStepZero)
+        ));
+        let result = privatize_with_mechanism(27, &LaplaceMechanism::new(0.0, 0.5), 1, &mut rng);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_privatize_and_suppress_matches_privatize() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut rng_privatize = StdRng::seed_from_u64(42);
+        let (mut suppressed, mut released) = (0, 0);
+        for _ in 0..1000 {
+            let result = privatize_and_suppress(10, 1.0, 0.5, 1, 10, &mut rng).unwrap();
+            let obfuscated = privatize(10, 1.0, 0.5, 1, &mut rng_privatize).unwrap();
+            if obfuscated < 10 {
+                assert_eq!(result, None);
+                suppressed += 1;
+            } else {
+                assert_eq!(result, Some(obfuscated));
+                released += 1;
+            }
+        }
+        assert!(suppressed > 0 && released > 0);
+    }
+
+    #[test]
+    fn test_privatize_unrounded_truncates() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut rng_rounded = StdRng::seed_from_u64(42);
+        let mut rng_unrounded = StdRng::seed_from_u64(42);
+        let mut differing = 0;
+        for _ in 0..1000 {
+            let noised = privatize_f64(1000.0, 1.0, 0.5, &mut rng).unwrap();
+            let rounded = privatize(1000, 1.0, 0.5, 1, &mut rng_rounded).unwrap();
+            let unrounded = privatize_unrounded(1000, 1.0, 0.5, &mut rng_unrounded).unwrap();
+            assert_eq!(unrounded, noised.trunc() as u64);
+            assert_eq!(rounded, noised.round() as u64);
+            if rounded != unrounded {
+                differing += 1;
+            }
+        }
+        assert!(differing > 300 && differing < 700);
+        assert_eq!(
+            privatize_unrounded(0, 1.0, f64::INFINITY, &mut rng).unwrap(),
+            0
+        );
+        assert!(privatize_unrounded(27, 1.0, 0.0, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_suggest_rounding_step() {
+        for (expected_max_value, step) in [
+            (0, 1),
+            (5, 1),
+            (199, 1),
+            (200, 2),
+            (499, 2),
+            (500, 5),
+            (1000, 10),
+            (1999, 10),
+            (2500, 20),
+            (75_000, 500),
+            (1_000_000, 10_000),
+            (u64::MAX, 100_000_000_000_000_000),
+        ] {
+            assert_eq!(suggest_rounding_step(expected_max_value), step);
+        }
+        for expected_max_value in [5, 200, 1000, 75_000, 1_000_000, u64::MAX] {
+            let step = suggest_rounding_step(expected_max_value);
+            assert!(check_rounding_step(step, expected_max_value).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_check_rounding_step() {
+        assert!(check_rounding_step(10, 1000).is_ok());
+        assert!(check_rounding_step(500, 1000).is_ok());
+        assert!(check_rounding_step(1, 0).is_err());
+        assert!(matches!(
+            check_rounding_step(1_000_000, 5),
+            Err(LaplaceError::StepTooLargeRelativeToValue {
+                rounding_step: 1_000_000,
+                expected_max_value: 5
+            })
+        ));
+        assert!(matches!(
+            check_rounding_step(501, 1000),
+            Err(LaplaceError::StepTooLargeRelativeToValue { .. })
+        ));
+        assert!(matches!(
+            check_rounding_step(0, 1000),
+            Err(LaplaceError::InvalidArgRoundingStepZero)
+        ));
+    }
+
+    #[test]
+    fn test_validate_params() {
+        assert!(validate_params(1.0, 0.1, 10).is_ok());
+        assert!(validate_params(1.0, f64::INFINITY, 1).is_ok());
+        assert!(matches!(
+            validate_params(1.0, 0.0, 1),
+            Err(LaplaceError::InvalidEpsilon(_))
+        ));
+        assert!(matches!(
+            validate_params(1.0, f64::NAN, 1),
+            Err(LaplaceError::InvalidEpsilon(_))
+        ));
+        assert!(matches!(
+            validate_params(-1.0, 1.0, 1),
+            Err(LaplaceError::InvalidSensitivity(_))
+        ));
+        assert!(matches!(
+            validate_params(f64::NAN, f64::INFINITY, 1),
+            Err(LaplaceError::InvalidEpsilon(_))
+        ));
+        assert!(matches!(
+            validate_params(0.0, 1.0, 1),
+            Err(LaplaceError::NonPositiveScale { .. })
+        ));
+        assert!(matches!(
+            validate_params(1e300, 1e-10, 1),
+            Err(LaplaceError::NonFiniteParameter { name: "b", .. })
+        ));
+        assert!(matches!(
+            validate_params(1.0, 1.0, 0),
+            Err(LaplaceError::InvalidArgRoundingStepZero)
+        ));
+    }
+
+    #[test]
+    fn test_validate_params_matches_privatize() {
+        let mut rng = rand::thread_rng();
+        for (sensitivity, epsilon, rounding_step) in [
+            (1.0, 0.1, 10),
+            (1.0, f64::INFINITY, 1),
+            (1.0, -1.0, 1),
+            (f64::INFINITY, 1.0, 1),
+            (0.0, 1.0, 1),
+            (1.0, 1.0, 0),
+        ] {
+            assert_eq!(
+                validate_params(sensitivity, epsilon, rounding_step).is_ok(),
+                privatize(27, sensitivity, epsilon, rounding_step, &mut rng).is_ok()
+            );
+        }
+    }
+
+    #[test]
+    fn test_privatize_batch_fails_fast() {
+        let mut rng = rand::thread_rng();
+        let values = [0; 100];
+        let bins = [1; 100];
+        let result = privatize_batch(
+            &values,
+            1.0,
+            0.0,
+            &bins,
+            None,
+            false,
+            ObfuscateBelow10Mode::Zero,
+            1,
+            &mut rng,
+        );
+        assert!(matches!(result, Err(LaplaceError::InvalidEpsilon(_))));
+    }
+
+    #[test]
+    fn test_below_10_obfuscate_never_wraps() {
+        let mut rng = rand::thread_rng();
+        let mut obfuscated = vec![];
+        for value in 0..10 {
+            for bin in 0..100 {
+                obfuscated.push(
+                    get_from_cache_or_privatize(
+                        value,
+                        1.0,
+                        0.1,
+                        bin,
+                        None,
+                        true,
+                        ObfuscateBelow10Mode::Obfuscate,
+                        1,
+                        &mut rng,
+                    )
+                    .unwrap(),
+                );
+            }
+        }
+        // negative noise is rounded to 0 instead of wrapping around, and noise with scale 10
+        // exceeds 1000 with a probability of about e^-99
+        assert!(obfuscated.iter().all(|&result| result < 1000));
+    }
+
+    #[test]
+    fn test_privatize_discrete_large_sensitivity() {
+        let mut rng = rand::thread_rng();
+        let result = privatize_discrete(-5, 10, 1.0, &mut rng);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_below_10_mode_from_str_round_trip() {
+        for mode in [
+            ObfuscateBelow10Mode::Zero,
+            ObfuscateBelow10Mode::Ten,
+            ObfuscateBelow10Mode::Obfuscate,
+        ] {
+            assert!(mode.to_string().parse::<ObfuscateBelow10Mode>().unwrap() == mode);
+        }
+        assert_eq!(ObfuscateBelow10Mode::Ten.to_string(), "ten");
+        let parsed = "Obfuscate".parse::<ObfuscateBelow10Mode>().unwrap();
+        assert!(parsed == ObfuscateBelow10Mode::Obfuscate);
+    }
+
+    #[test]
+    fn test_below_10_mode_from_str_unknown() {
+        let result = "eleven".parse::<ObfuscateBelow10Mode>();
+        assert!(matches!(result, Err(LaplaceError::UnknownMode(mode)) if mode == "eleven"));
+    }
+
+    #[test]
+    fn test_small_count_policy_from_below_10_mode() {
+        let policy = SmallCountPolicy::from(ObfuscateBelow10Mode::Ten);
+        assert_eq!(policy.threshold, 10);
+        assert_eq!(policy.mode, SuppressionMode::Clamp(10));
+        assert_eq!(
+            SmallCountPolicy::from(ObfuscateBelow10Mode::Zero).mode,
+            SuppressionMode::Zero
+        );
+        assert_eq!(
+            SmallCountPolicy::from(ObfuscateBelow10Mode::Obfuscate).mode,
+            SuppressionMode::Obfuscate
+        );
+    }
+
+    #[test]
+    fn test_small_count_policy_suppress() {
+        let zero = SmallCountPolicy {
+            threshold: 5,
+            mode: SuppressionMode::Zero,
+        };
+        assert_eq!(zero.suppress(4), Some(0));
+        assert_eq!(zero.suppress(5), None);
+
+        let clamp = SmallCountPolicy {
+            threshold: 20,
+            mode: SuppressionMode::Clamp(20),
+        };
+        assert_eq!(clamp.suppress(0), Some(20));
+        assert_eq!(clamp.suppress(19), Some(20));
+        assert_eq!(clamp.suppress(20), None);
+
+        let obfuscate = SmallCountPolicy {
+            threshold: 20,
+            mode: SuppressionMode::Obfuscate,
+        };
+        assert_eq!(obfuscate.suppress(3), None);
+    }
+
+    #[test]
+    fn test_round_parametric() {
+        assert_eq!(round_parametric(3.2, 1).unwrap(), 3);
+        assert_eq!(round_parametric(3.7, 1).unwrap(), 4);
+        assert_eq!(round_parametric(12.8, 5).unwrap(), 15);
+        assert_eq!(round_parametric(17.4, 5).unwrap(), 15);
+        assert_eq!(round_parametric(38.2, 10).unwrap(), 40);
+        assert_eq!(round_parametric(44.9, 10).unwrap(), 40);
+    }
+
+    #[test]
+    fn test_round_parametric_zero() {
+        assert_eq!(round_parametric(0.0, 1).unwrap(), 0);
+        assert_eq!(round_parametric(0.0, 5).unwrap(), 0);
+        assert_eq!(round_parametric(0.0, 10).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_round_parametric_large() {
+        assert_eq!(round_parametric(1_000_000.0, 1).unwrap(), 1_000_000);
+        assert_eq!(round_parametric(1_000_000.0, 5).unwrap(), 1_000_000);
+        assert_eq!(round_parametric(1_000_000.0, 10).unwrap(), 1_000_000);
+    }
+
+    #[test]
+    fn test_round_parametric_negative() {
+        assert_eq!(round_parametric(-5.0, 10).unwrap(), 0);
+        assert_eq!(round_parametric(-1e30, 1).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_round_parametric_non_finite() {
+        let mut rng = rand::thread_rng();
+        for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(matches!(
+                round_parametric(value, 5),
+                Err(LaplaceError::NonFiniteParameter { name: "value", .. })
+            ));
+            assert!(matches!(
+                round_parametric_i64(value, 5),
+                Err(LaplaceError::NonFiniteParameter { name: "value", .. })
+            ));
+            assert!(matches!(
+                round_parametric_with_mode(value, 5, RoundingMode::Floor, &mut rng),
+                Err(LaplaceError::NonFiniteParameter { name: "value", .. })
+            ));
+            assert!(privatize_with_injected_noise(27, value, 1).is_err());
+        }
+    }
+
+    #[test]
+    fn test_round_parametric_near_u64_max() {
+        assert_eq!(round_parametric(u64::MAX as f64, 1).unwrap(), u64::MAX);
+        assert_eq!(
+            round_parametric(u64::MAX as f64, 10).unwrap(),
+            u64::MAX / 10 * 10
+        );
+        assert_eq!(round_parametric(1e30, 5).unwrap(), u64::MAX / 5 * 5);
+        let mut rng = rand::thread_rng();
+        let rounded = round_parametric_with_mode(u64::MAX as f64, 10, RoundingMode::Ceil, &mut rng);
+        assert_eq!(rounded.unwrap(), u64::MAX / 10 * 10);
+    }
+
+    #[test]
+    fn test_round_parametric_invalid_step() {
+        let result = round_parametric(10.0, 0);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_round_parametric_with_mode() {
+        let mut rng = rand::thread_rng();
+        assert_eq!(
+            round_parametric_with_mode(12.8, 5, RoundingMode::Nearest, &mut rng).unwrap(),
+            15
+        );
+        assert_eq!(
+            round_parametric_with_mode(12.8, 5, RoundingMode::Floor, &mut rng).unwrap(),
+            10
+        );
+        assert_eq!(
+            round_parametric_with_mode(12.8, 5, RoundingMode::Ceil, &mut rng).unwrap(),
+            15
+        );
+        assert_eq!(
+            round_parametric_with_mode(11.2, 5, RoundingMode::Nearest, &mut rng).unwrap(),
+            10
+        );
+        assert_eq!(
+            round_parametric_with_mode(11.2, 5, RoundingMode::Floor, &mut rng).unwrap(),
+            10
+        );
+        assert_eq!(
+            round_parametric_with_mode(11.2, 5, RoundingMode::Ceil, &mut rng).unwrap(),
+            15
+        );
+        assert_eq!(
+            round_parametric_with_mode(10.0, 5, RoundingMode::Floor, &mut rng).unwrap(),
+            10
+        );
+        assert_eq!(
+            round_parametric_with_mode(10.0, 5, RoundingMode::Ceil, &mut rng).unwrap(),
+            10
+        );
+    }
+
+    #[test]
+    fn test_round_parametric_with_mode_invalid_step() {
+        let mut rng = rand::thread_rng();
+        for mode in [
+            RoundingMode::Nearest,
+            RoundingMode::Floor,
+            RoundingMode::Ceil,
+            RoundingMode::Stochastic,
+        ] {
+            assert!(round_parametric_with_mode(10.0, 0, mode, &mut rng).is_err());
+        }
+    }
+
+    #[test]
+    fn test_round_parametric_stochastic_multiples() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let result = round_parametric_with_mode(12.5, 5, RoundingMode::Stochastic, &mut rng);
+            assert!(matches!(result, Ok(10) | Ok(15)));
+        }
+        let result = round_parametric_with_mode(15.0, 5, RoundingMode::Stochastic, &mut rng);
+        assert_eq!(result.unwrap(), 15);
+    }
+
+    #[test]
+    fn test_round_parametric_stochastic_unbiased() {
+        let mut rng = StdRng::seed_from_u64(17);
+        let samples = 100_000;
+        for value in [12.3, 12.5, 14.9] {
+            let sum: u64 = (0..samples)
+                .map(|_| {
+                    round_parametric_with_mode(value, 5, RoundingMode::Stochastic, &mut rng)
+                        .unwrap()
+                })
+                .sum();
+            let mean = sum as f64 / samples as f64;
+            assert!((mean - value).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_round_parametric_i64_negative() {
+        assert_eq!(round_parametric_i64(-3.2, 1).unwrap(), -3);
+        assert_eq!(round_parametric_i64(-3.7, 1).unwrap(), -4);
+        assert_eq!(round_parametric_i64(-12.8, 5).unwrap(), -15);
+        assert_eq!(round_parametric_i64(-17.4, 5).unwrap(), -15);
+        assert_eq!(round_parametric_i64(-44.9, 10).unwrap(), -40);
+    }
+
+    #[test]
+    fn test_round_parametric_i64_near_zero() {
+        assert_eq!(round_parametric_i64(0.0, 1).unwrap(), 0);
+        assert_eq!(round_parametric_i64(0.4, 1).unwrap(), 0);
+        assert_eq!(round_parametric_i64(-0.4, 1).unwrap(), 0);
+        assert_eq!(round_parametric_i64(2.4, 5).unwrap(), 0);
+        assert_eq!(round_parametric_i64(-2.4, 5).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_round_parametric_i64_crossing_zero() {
+        assert_eq!(round_parametric_i64(-0.6, 1).unwrap(), -1);
+        assert_eq!(round_parametric_i64(0.6, 1).unwrap(), 1);
+        assert_eq!(round_parametric_i64(-2.6, 5).unwrap(), -5);
+        assert_eq!(round_parametric_i64(2.6, 5).unwrap(), 5);
+    }
+
+    #[test]
+    fn test_round_parametric_i64_invalid_step() {
+        let result = round_parametric_i64(-10.0, 0);
+        assert!(result.is_err());
+        let result = round_parametric_i64(10.0, i64::MAX as usize + 1);
+        assert!(matches!(result, Err(LaplaceError::RoundingStepError(_))));
+    }
+
+    #[test]
+    fn test_round_parametric_i64_saturates() {
+        assert_eq!(round_parametric_i64(i64::MAX as f64, 1).unwrap(), i64::MAX);
+        assert_eq!(round_parametric_i64(i64::MIN as f64, 1).unwrap(), -i64::MAX);
+        assert_eq!(round_parametric_i64(1e30, 10).unwrap(), i64::MAX / 10 * 10);
+        assert_eq!(
+            round_parametric_i64(-1e30, 10).unwrap(),
+            -(i64::MAX / 10 * 10)
+        );
+        let huge_step = i64::MAX as usize;
+        assert_eq!(round_parametric_i64(1e30, huge_step).unwrap(), i64::MAX);
+        assert_eq!(round_parametric_i64(-1e30, huge_step).unwrap(), -i64::MAX);
+        assert_eq!(round_parametric_i64(1e9, huge_step).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_privatize_i64_precision_loss() {
+        let mut rng = rand::thread_rng();
+        for value in [i64::MIN, i64::MAX, -(MAX_EXACT_VALUE as i64) - 1] {
+            let result = privatize_i64(value, 1.0, 1.0, 1, &mut rng);
+            assert!(matches!(result, Err(LaplaceError::PrecisionLoss(_))));
+        }
+        let exact = MAX_EXACT_VALUE as i64;
+        assert!(privatize_i64(exact, 1.0, f64::INFINITY, 1, &mut rng).is_ok());
+        assert_eq!(
+            privatize_i64(-exact, 1.0, f64::INFINITY, 1, &mut rng).unwrap(),
+            -exact
+        );
+    }
+
+    #[test]
+    fn test_privatize_i64_negative() {
+        let mut rng = rand::thread_rng();
+        let result = privatize_i64(-1000, 1.0, 1.0, 10, &mut rng).unwrap();
+        assert!(result < 0);
+        assert_eq!(result % 10, 0);
+    }
+
+    #[test]
+    fn test_privatize_invalid_epsilon() {
+        let mut rng = rand::thread_rng();
+        for epsilon in [0.0, -1.0, f64::NAN, f64::NEG_INFINITY] {
+            let result = privatize(27, 1.0, epsilon, 10, &mut rng);
+            assert!(matches!(result, Err(LaplaceError::InvalidEpsilon(_))));
+        }
+    }
+
+    #[test]
+    fn test_privatize_invalid_sensitivity() {
+        let mut rng = rand::thread_rng();
+        for sensitivity in [-1.0, f64::NAN, f64::INFINITY] {
+            let result = privatize(27, sensitivity, 1.0, 10, &mut rng);
+            assert!(matches!(result, Err(LaplaceError::InvalidSensitivity(_))));
+        }
+    }
+
+    #[test]
+    fn test_laplace_scale() {
+        assert_eq!(laplace_scale(1.0, 1.0).unwrap(), 1.0);
+        assert_eq!(laplace_scale(2.0, 0.5).unwrap(), 4.0);
+        assert_eq!(laplace_scale(10.0, 0.1).unwrap(), 100.0);
+        assert_eq!(laplace_scale(0.0, 0.5).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_scale_epsilon_inverse() {
+        for sensitivity in [0.5, 1.0, 10.0, 1e6] {
+            for epsilon in [1e-3, 0.1, 0.5, 1.0, 7.3] {
+                let b = scale_from_epsilon(sensitivity, epsilon).unwrap();
+                let recovered = epsilon_from_scale(sensitivity, b).unwrap();
+                assert!((recovered / epsilon - 1.0).abs() < 1e-12);
+                let recovered =
+                    scale_from_epsilon(sensitivity, epsilon_from_scale(sensitivity, b).unwrap())
+                        .unwrap();
+                assert!((recovered / b - 1.0).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_scale_epsilon_invalid() {
+        assert!(matches!(
+            scale_from_epsilon(1.0, 0.0),
+            Err(LaplaceError::InvalidEpsilon(_))
+        ));
+        assert!(matches!(
+            scale_from_epsilon(0.0, 1.0),
+            Err(LaplaceError::NonPositiveScale { .. })
+        ));
+        assert!(matches!(
+            epsilon_from_scale(1.0, 0.0),
+            Err(LaplaceError::NonPositiveScale { .. })
+        ));
+        assert!(matches!(
+            epsilon_from_scale(1.0, f64::INFINITY),
+            Err(LaplaceError::NonFiniteParameter { name: "b", .. })
+        ));
+        assert!(matches!(
+            epsilon_from_scale(0.0, 1.0),
+            Err(LaplaceError::InvalidEpsilon(_))
+        ));
+        assert!(matches!(
+            epsilon_from_scale(-1.0, 1.0),
+            Err(LaplaceError::InvalidEpsilon(_))
+        ));
+    }
+
+    #[test]
+    fn test_laplace_scale_invalid() {
+        assert!(matches!(
+            laplace_scale(1.0, 0.0),
+            Err(LaplaceError::InvalidEpsilon(_))
+        ));
+        assert!(matches!(
+            laplace_scale(1.0, -0.5),
+            Err(LaplaceError::InvalidEpsilon(_))
+        ));
+        assert!(matches!(
+            laplace_scale(-1.0, 0.5),
+            Err(LaplaceError::InvalidSensitivity(_))
+        ));
+    }
+
+    #[test]
+    fn test_privatize_ok() {
+        let mut rng = rand::thread_rng();
+        let value = 27;
+        let sensitivity = 10.0;
+        let epsilon = 0.5;
+        let rounding_step = 10;
+        let result = privatize(value, sensitivity, epsilon, rounding_step, &mut rng);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_privatize_clamped_zero_large_sensitivity() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let result = privatize_clamped(0, 10.0, 0.5, 1, &mut rng).unwrap();
+            assert!(result < 10_000);
+        }
+    }
+
+    #[test]
+    fn test_privatize_clamped_small_value() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let result = privatize_clamped(2, 10.0, 0.5, 10, &mut rng).unwrap();
+            assert!(result < 10_000);
+            assert_eq!(result % 10, 0);
+        }
+    }
+
+    #[test]
+    fn test_privatize_gaussian_ok() {
+        let mut rng = rand::thread_rng();
+        let result = privatize_gaussian(27, 1.0, 0.5, 1e-5, 10, &mut rng);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap() % 10, 0);
+    }
+
+    #[test]
+    fn test_privatize_gaussian_invalid_delta() {
+        let mut rng = rand::thread_rng();
+        let result = privatize_gaussian(27, 1.0, 0.5, 0.0, 10, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::InvalidDelta(_))));
+    }
+
+    #[test]
+    fn test_snapping_granularity() {
+        assert_eq!(snapping_granularity(1.0), 1.0);
+        assert_eq!(snapping_granularity(1.5), 2.0);
+        assert_eq!(snapping_granularity(2.0), 2.0);
+        assert_eq!(snapping_granularity(20.0), 32.0);
+        assert_eq!(snapping_granularity(0.3), 0.5);
+        assert_eq!(snapping_granularity(0.125), 0.125);
+    }
+
+    #[test]
+    fn test_uniform_open_closed_52() {
+        let mut rng = rand::rngs::mock::StepRng::new(0, 0);
+        assert_eq!(uniform_open_closed_52(&mut rng), 1.0);
+        let mut rng = rand::rngs::mock::StepRng::new(u64::MAX, 0);
+        assert_eq!(uniform_open_closed_52(&mut rng), 2f64.powi(-52));
+        let mut rng = rand::rngs::mock::StepRng::new(1 << 12, 1 << 12);
+        assert_eq!(uniform_open_closed_52(&mut rng), 1.0 - 2f64.powi(-52));
+        assert_eq!(uniform_open_closed_52(&mut rng), 1.0 - 2f64.powi(-51));
+    }
+
+    #[test]
+    fn test_privatize_snapping_on_grid() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let result = privatize_snapping(27.3, 10.0, 0.5, 1000.0, &mut rng).unwrap();
+            assert_eq!(result % 32.0, 0.0);
+            assert!((-1000.0..=1000.0).contains(&result));
+        }
+    }
+
+    #[test]
+    fn test_privatize_snapping_bound() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let result = privatize_snapping(1e9, 1.0, 1.0, 100.0, &mut rng).unwrap();
+            assert!((-100.0..=100.0).contains(&result));
+        }
+    }
+
+    #[test]
+    fn test_privatize_snapping_invalid_bound() {
+        let mut rng = rand::thread_rng();
+        let result = privatize_snapping(1.0, 1.0, 1.0, 0.0, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::InvalidBound(_))));
+        let result = privatize_snapping(1.0, 1.0, 1.0, f64::INFINITY, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::InvalidBound(_))));
+    }
+
+    #[test]
+    fn test_privatize_snapping_bound_precondition() {
+        let mut rng = rand::thread_rng();
+        // the scale is slightly above 20, so a bound of 20 is too small
+        let result = privatize_snapping(1.0, 10.0, 0.5, 20.0, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::InvalidBound(_))));
+        let result = privatize_snapping(1.0, 1.0, 1.0, 2f64.powi(47), &mut rng);
+        assert!(matches!(result, Err(LaplaceError::InvalidBound(_))));
+        assert!(privatize_snapping(1.0, 10.0, 0.5, 21.0, &mut rng).is_ok());
+    }
+
+    #[test]
+    fn test_privatize_snapping_non_finite_value() {
+        let mut rng = rand::thread_rng();
+        for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let result = privatize_snapping(value, 1.0, 1.0, 100.0, &mut rng);
+            assert!(matches!(
+                result,
+                Err(LaplaceError::NonFiniteParameter { name: "value", .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_privatize_truncated_within_domain() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let result = privatize_truncated(25, 10.0, 0.5, 27, 1, &mut rng).unwrap();
+            assert!(result <= 27);
+            let result = privatize_truncated(25, 10.0, 0.5, 27, 10, &mut rng).unwrap();
+            assert!(result <= 20);
+            assert_eq!(result % 10, 0);
+        }
+    }
+
+    #[test]
+    fn test_privatize_truncated_zero_domain() {
+        let mut rng = rand::thread_rng();
+        let result = privatize_truncated(0, 1.0, 1.0, 0, 1, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::InvalidDomain)));
+    }
+
+    #[test]
+    fn test_privatize_with_distribution_normal() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut rng_noise = StdRng::seed_from_u64(42);
+        let normal = statrs::distribution::Normal::new(0.0, 20.0).unwrap();
+        for value in [0, 27, 1000] {
+            let result = privatize_with_distribution(value, &normal, 5, None, &mut rng).unwrap();
+            let noise = normal.sample(&mut rng_noise);
+            assert_eq!(
+                result,
+                privatize_with_injected_noise(value, noise, 5).unwrap()
+            );
+        }
+        for _ in 0..1000 {
+            let result = privatize_with_distribution(25, &normal, 10, Some(27), &mut rng).unwrap();
+            assert!(result <= 20);
+            assert_eq!(result % 10, 0);
+        }
+        let result = privatize_with_distribution(25, &normal, 0, None, &mut rng);
+        assert!(matches!(
+            result,
+            Err(LaplaceError::InvalidArgRoundingStepZero)
+        ));
+        let result = privatize_with_distribution(25, &normal, 1, Some(0), &mut rng);
+        assert!(matches!(result, Err(LaplaceError::InvalidDomain)));
+    }
+
+    #[test]
+    fn test_privatize_f64_ok() {
+        let mut rng = rand::thread_rng();
+        let result = privatize_f64(0.25, 1.0, 1.0, &mut rng);
+        assert!(result.is_ok());
+        assert!(result.unwrap().is_finite());
+    }
+
+    #[test]
+    fn test_privatize_f64_err() {
+        let mut rng = rand::thread_rng();
+        let result = privatize_f64(0.25, 0.0, 1.0, &mut rng);
         assert!(result.is_err());
     }
 
+    // the intended error handling: obfuscation errors are propagated with `?` and handled once
+    // by the caller, instead of ignoring the `Result`
+    fn obfuscate_report(values: &[u64], epsilon: f64) -> Result<Vec<u64>, LaplaceError> {
+        let mut rng = rand::thread_rng();
+        let mut report = Vec::with_capacity(values.len());
+        for &value in values {
+            report.push(privatize(value, 1.0, epsilon, 10, &mut rng)?);
+        }
+        Ok(report)
+    }
+
+    #[test]
+    fn test_error_handling_flow() {
+        match obfuscate_report(&[10, 20, 30], 1.0) {
+            Ok(report) => assert_eq!(report.len(), 3),
+            Err(err) => panic!("unexpected error: {err}"),
+        }
+        match obfuscate_report(&[10, 20, 30], 0.0) {
+            Err(LaplaceError::InvalidEpsilon(epsilon)) => assert_eq!(epsilon, 0.0),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_privatize_infinite_epsilon() {
+        let mut rng = rand::thread_rng();
+        for value in [0, 7, 27, 1000] {
+            assert_eq!(
+                privatize(value, 1.0, f64::INFINITY, 10, &mut rng).unwrap(),
+                round_parametric(value as f64, 10).unwrap()
+            );
+        }
+        let (obfuscated, noise) =
+            privatize_with_noise(27, 1.0, f64::INFINITY, 1, &mut rng).unwrap();
+        assert_eq!((obfuscated, noise), (27, 0.0));
+    }
+
+    #[test]
+    fn test_privatize_with_noise() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for value in [0, 5, 27, 1000] {
+            let (obfuscated, noise) = privatize_with_noise(value, 1.0, 0.1, 10, &mut rng).unwrap();
+            assert_eq!(
+                obfuscated,
+                round_parametric(value as f64 + noise, 10).unwrap()
+            );
+        }
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut rng_with_noise = StdRng::seed_from_u64(7);
+        assert_eq!(
+            privatize(27, 1.0, 0.1, 1, &mut rng).unwrap(),
+            privatize_with_noise(27, 1.0, 0.1, 1, &mut rng_with_noise)
+                .unwrap()
+                .0
+        );
+    }
+
+    #[test]
+    fn test_privatize_u32_near_max() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let result = privatize_u32(u32::MAX, 10.0, 0.1, 1, &mut rng).unwrap();
+            assert!(result > u32::MAX - 10_000);
+            let result = privatize_u32(u32::MAX - 5, 10.0, 0.1, 10, &mut rng).unwrap();
+            assert!(result <= u32::MAX / 10 * 10);
+            assert_eq!(result % 10, 0);
+        }
+    }
+
+    #[test]
+    fn test_privatize_u16() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut rng_u64 = StdRng::seed_from_u64(42);
+        let result = privatize_u16(27, 1.0, 0.1, 5, &mut rng).unwrap();
+        assert_eq!(
+            u64::from(result),
+            privatize(27, 1.0, 0.1, 5, &mut rng_u64).unwrap()
+        );
+        let result = privatize_u16(u16::MAX, 1.0, 0.01, 1, &mut rng).unwrap();
+        assert!(result > u16::MAX - 5000);
+    }
+
+    #[test]
+    fn test_privatize_with_injected_noise() {
+        assert_eq!(privatize_with_injected_noise(27, 0.0, 1).unwrap(), 27);
+        assert_eq!(privatize_with_injected_noise(27, 2.6, 1).unwrap(), 30);
+        assert_eq!(privatize_with_injected_noise(27, -4.9, 10).unwrap(), 20);
+        assert_eq!(privatize_with_injected_noise(27, 8.1, 10).unwrap(), 40);
+        assert_eq!(privatize_with_injected_noise(3, -10.0, 1).unwrap(), 0);
+        assert!(matches!(
+            privatize_with_injected_noise(27, 1.0, 0),
+            Err(LaplaceError::InvalidArgRoundingStepZero)
+        ));
+    }
+
+    #[test]
+    fn test_privatize_with_rounder() {
+        let rounder = |value: f64| {
+            if value < 100.0 {
+                round_parametric(value, 1)
+            } else {
+                round_parametric(value, 10)
+            }
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..1000 {
+            let result = privatize_with_rounder(1000, 1.0, 1.0, rounder, &mut rng).unwrap();
+            assert_eq!(result % 10, 0);
+        }
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut rng_rounder = StdRng::seed_from_u64(7);
+        assert_eq!(
+            privatize(27, 1.0, 0.1, 5, &mut rng).unwrap(),
+            privatize_with_rounder(
+                27,
+                1.0,
+                0.1,
+                |value| round_parametric(value, 5),
+                &mut rng_rounder
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_privatize_with_rounder_error() {
+        let mut rng = rand::thread_rng();
+        let rounder = |_| Err(LaplaceError::InvalidArgRoundingStepZero);
+        let result = privatize_with_rounder(27, 1.0, 1.0, rounder, &mut rng);
+        assert!(matches!(
+            result,
+            Err(LaplaceError::InvalidArgRoundingStepZero)
+        ));
+    }
+
+    #[test]
+    fn test_privatize_rate_within_unit_interval() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let rate = privatize_rate(3, 10, 0.1, &mut rng).unwrap();
+            assert!((0.0..=1.0).contains(&rate));
+            let rate = privatize_rate(10, 10, 1.0, &mut rng).unwrap();
+            assert!((0.0..=1.0).contains(&rate));
+        }
+    }
+
+    #[test]
+    fn test_privatize_rate_zero_denominator() {
+        let mut rng = rand::thread_rng();
+        let result = privatize_rate(0, 0, 1.0, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::ZeroDenominator)));
+    }
+
+    #[test]
+    fn test_privatize_rate_precision_loss() {
+        let mut rng = rand::thread_rng();
+        let too_large = MAX_EXACT_VALUE + 1;
+        let result = privatize_rate(too_large, u64::MAX, 1.0, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::PrecisionLoss(_))));
+        let result = privatize_rate(1, too_large, 1.0, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::PrecisionLoss(value)) if value == too_large));
+        assert!(privatize_rate(MAX_EXACT_VALUE, MAX_EXACT_VALUE, 1.0, &mut rng).is_ok());
+    }
+
     #[test]
-    fn test_laplace_ok() {
-        let mu = 10.0;
-        let b = 1.0;
+    fn test_privatize_sum_clips_contributions() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut rng_expected = StdRng::seed_from_u64(42);
+        let result = privatize_sum(&[1e9, 2.0, -1e9, -1.0], 5.0, 0.5, &mut rng).unwrap();
+        let expected = privatize_f64(1.0, 5.0, 0.5, &mut rng_expected).unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_privatize_sum_invalid() {
         let mut rng = rand::thread_rng();
-        let result = laplace(mu, b, &mut rng);
-        assert!(result.is_ok());
+        let result = privatize_sum(&[1.0, f64::NAN], 5.0, 0.5, &mut rng);
+        assert!(matches!(
+            result,
+            Err(LaplaceError::NonFiniteParameter { name: "value", .. })
+        ));
+        let result = privatize_sum(&[1.0], f64::NAN, 0.5, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::InvalidSensitivity(_))));
     }
 
     #[test]
-    fn test_laplace_output_within_range() {
-        let mu = 10.0;
-        let b = 1.0;
+    fn test_privatize_mean_close_to_true_mean() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let values: Vec<f64> = (0..10_000).map(|i| (i % 10) as f64).collect();
+        let mean = privatize_mean(&values, 10.0, 1.0, &mut rng).unwrap();
+        assert!((mean - 4.5).abs() < 0.1, "mean {mean}");
+    }
+
+    #[test]
+    fn test_privatize_mean_within_clip() {
         let mut rng = rand::thread_rng();
-        let result = laplace(mu, b, &mut rng).unwrap();
-        assert!(result >= mu - 10.0 * b && result <= mu + 10.0 * b);
+        for _ in 0..1000 {
+            let mean = privatize_mean(&[], 5.0, 0.1, &mut rng).unwrap();
+            assert!((-5.0..=5.0).contains(&mean));
+        }
     }
 
     #[test]
-    fn test_round_parametric() {
-        assert_eq!(round_parametric(3.2, 1).unwrap(), 3);
-        assert_eq!(round_parametric(3.7, 1).unwrap(), 4);
-        assert_eq!(round_parametric(12.8, 5).unwrap(), 15);
-        assert_eq!(round_parametric(17.4, 5).unwrap(), 15);
-        assert_eq!(round_parametric(38.2, 10).unwrap(), 40);
-        assert_eq!(round_parametric(44.9, 10).unwrap(), 40);
+    fn test_privatize_bounded_within_bounds() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let result = privatize_bounded(2.0, 1.0, 0.5, 0.0, 10.0, &mut rng).unwrap();
+            assert!((0.0..=10.0).contains(&result));
+        }
     }
 
     #[test]
-    fn test_round_parametric_zero() {
-        assert_eq!(round_parametric(0.0, 1).unwrap(), 0);
-        assert_eq!(round_parametric(0.0, 5).unwrap(), 0);
-        assert_eq!(round_parametric(0.0, 10).unwrap(), 0);
+    fn test_privatize_bounded_doubles_scale() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut reference_rng = StdRng::seed_from_u64(7);
+        let mechanism = LaplaceMechanism::new(2.0, 0.5);
+        for _ in 0..100 {
+            let result = privatize_bounded(2.0, 1.0, 0.5, -1e9, 1e9, &mut rng).unwrap();
+            let expected = mechanism.perturb(2.0, &mut reference_rng).unwrap();
+            assert_eq!(result, expected);
+        }
     }
 
     #[test]
-    fn test_round_parametric_large() {
-        assert_eq!(round_parametric(1_000_000.0, 1).unwrap(), 1_000_000);
-        assert_eq!(round_parametric(1_000_000.0, 5).unwrap(), 1_000_000);
-        assert_eq!(round_parametric(1_000_000.0, 10).unwrap(), 1_000_000);
+    fn test_privatize_bounded_invalid_bounds() {
+        let mut rng = rand::thread_rng();
+        for (lower, upper) in [(10.0, 0.0), (5.0, 5.0), (f64::NAN, 1.0)] {
+            let result = privatize_bounded(2.0, 1.0, 1.0, lower, upper, &mut rng);
+            assert!(matches!(result, Err(LaplaceError::InvalidOutputBounds(..))));
+        }
     }
 
     #[test]
-    fn test_round_parametric_invalid_step() {
-        let result = round_parametric(10.0, 0);
-        assert!(result.is_err());
+    fn test_privatize_bounded_resampling_exhausted() {
+        let mut rng = rand::thread_rng();
+        let result = privatize_bounded(0.0, 1.0, 1.0, 1e6, 1e6 + 1.0, &mut rng);
+        assert!(matches!(
+            result,
+            Err(LaplaceError::ResamplingExhausted(MAX_RESAMPLING_ATTEMPTS))
+        ));
     }
 
     #[test]
-    fn test_privatize_ok() {
+    fn test_obfuscate_value_zero() {
         let mut rng = rand::thread_rng();
-        let value = 27;
-        let sensitivity = 10.0;
-        let epsilon = 0.5;
-        let rounding_step = 10;
-        let result = privatize(
-            value,
-            sensitivity,
-            epsilon,
-            rounding_step,
+        let result = get_from_cache_or_privatize(
+            0,
+            1.0,
+            1.0,
+            1,
+            None,
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
             &mut rng,
         );
+
         assert!(result.is_ok());
     }
 
     #[test]
-    fn test_obfuscate_value_zero() {
+    fn test_obfuscate_value_non_zero() {
         let mut rng = rand::thread_rng();
-        let result = get_from_cache_or_privatize(0, 1.0, 1.0, 1, None, true, ObfuscateBelow10Mode::Obfuscate, 1, &mut rng);
+        let result = get_from_cache_or_privatize(
+            10,
+            1.0,
+            1.0,
+            1,
+            None,
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        );
 
         assert!(result.is_ok());
     }
 
     #[test]
-    fn test_obfuscate_value_non_zero() {
+    fn test_obfuscate_without_cache_zero_not_obfuscated() {
         let mut rng = rand::thread_rng();
-        let result = get_from_cache_or_privatize(10, 1.0, 1.0, 1, None, true, ObfuscateBelow10Mode::Obfuscate, 1, &mut rng);
+        for _ in 0..100 {
+            let result = get_from_cache_or_privatize(
+                0,
+                1.0,
+                0.1,
+                1,
+                None,
+                false,
+                ObfuscateBelow10Mode::Obfuscate,
+                1,
+                &mut rng,
+            );
+            assert_eq!(result.unwrap(), 0);
+        }
+    }
 
-        assert!(result.is_ok());
+    #[test]
+    fn test_obfuscate_without_cache_below_10() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let result = get_from_cache_or_privatize(
+                5,
+                1.0,
+                0.1,
+                1,
+                None,
+                true,
+                ObfuscateBelow10Mode::Zero,
+                1,
+                &mut rng,
+            );
+            assert_eq!(result.unwrap(), 0);
+            let result = get_from_cache_or_privatize(
+                5,
+                1.0,
+                0.1,
+                1,
+                None,
+                true,
+                ObfuscateBelow10Mode::Ten,
+                1,
+                &mut rng,
+            );
+            assert_eq!(result.unwrap(), 10);
+        }
+    }
+
+    #[test]
+    fn test_obfuscate_invalid_epsilon_err() {
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::new();
+        let result = get_from_cache_or_privatize(
+            27,
+            1.0,
+            0.0,
+            1,
+            None,
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        );
+        assert!(matches!(result, Err(LaplaceError::InvalidEpsilon(_))));
+        let result = get_from_cache_or_privatize(
+            27,
+            1.0,
+            0.0,
+            1,
+            Some(&mut obf_cache),
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        );
+        assert!(matches!(result, Err(LaplaceError::InvalidEpsilon(_))));
+        assert!(obf_cache.is_empty());
     }
 
     #[test]
     fn test_with_obf_cache() {
         let mut rng = rand::thread_rng();
-        let mut obf_cache: ObfCache = ObfCache {
-            cache: HashMap::new(),
-        };
+        let mut obf_cache = ObfCache::new();
 
-        let result =
-            get_from_cache_or_privatize(10, 1.0, 1.0, 1, Some(&mut obf_cache), true, ObfuscateBelow10Mode::Obfuscate, 1, &mut rng);
+        let result = get_from_cache_or_privatize(
+            10,
+            1.0,
+            1.0,
+            1,
+            Some(&mut obf_cache),
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        );
         assert!(result.is_ok());
 
-        let obfuscated_value = obf_cache.cache.get(&(1, 10, 1));
+        let obfuscated_value = obf_cache.cache.get(&key(1.0, 1.0, 10, 1));
         assert!(obfuscated_value.is_some());
         let result_ok = result.unwrap();
         assert_eq!(result_ok.clone(), *obfuscated_value.unwrap());
 
-        let result2 =
-            get_from_cache_or_privatize(10, 1.0, 1.0, 1, Some(&mut obf_cache), true, ObfuscateBelow10Mode::Obfuscate, 1, &mut rng);
+        let result2 = get_from_cache_or_privatize(
+            10,
+            1.0,
+            1.0,
+            1,
+            Some(&mut obf_cache),
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        );
         assert!(result2.is_ok());
         assert_eq!(result_ok, result2.unwrap());
     }
+
+    #[test]
+    fn test_obf_cache_new() {
+        let obf_cache = ObfCache::new();
+        assert!(obf_cache.is_empty());
+        assert_eq!(obf_cache.len(), 0);
+        assert_eq!(obf_cache.max_entries(), None);
+    }
+
+    #[test]
+    fn test_obf_cache_len() {
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::default();
+        for value in [10, 20, 10] {
+            get_from_cache_or_privatize(
+                value,
+                1.0,
+                1.0,
+                1,
+                Some(&mut obf_cache),
+                true,
+                ObfuscateBelow10Mode::Obfuscate,
+                1,
+                &mut rng,
+            )
+            .unwrap();
+        }
+        assert_eq!(obf_cache.len(), 2);
+        assert!(!obf_cache.is_empty());
+    }
+
+    #[test]
+    fn test_obf_cache_clear() {
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::with_capacity(2).unwrap();
+        for value in [10, 20] {
+            get_from_cache_or_privatize(
+                value,
+                1.0,
+                1.0,
+                1,
+                Some(&mut obf_cache),
+                true,
+                ObfuscateBelow10Mode::Obfuscate,
+                1,
+                &mut rng,
+            )
+            .unwrap();
+        }
+        obf_cache.clear();
+        assert!(obf_cache.is_empty());
+        assert_eq!(obf_cache.max_entries(), Some(2));
+
+        for value in [30, 40] {
+            get_from_cache_or_privatize(
+                value,
+                1.0,
+                1.0,
+                1,
+                Some(&mut obf_cache),
+                true,
+                ObfuscateBelow10Mode::Obfuscate,
+                1,
+                &mut rng,
+            )
+            .unwrap();
+        }
+        assert_eq!(obf_cache.len(), 2);
+    }
+
+    #[test]
+    fn test_obf_cache_purge_older_than() {
+        let day = Duration::from_secs(24 * 60 * 60);
+        let mut obf_cache = ObfCache::with_capacity(3).unwrap();
+        obf_cache.insert(key(1.0, 1.0, 10, 1), 10);
+        obf_cache.insert(key(1.0, 1.0, 20, 1), 20);
+        obf_cache.insert(key(1.0, 1.0, 30, 1), 30);
+        let stale = Instant::now().checked_sub(2 * day).unwrap();
+        obf_cache.inserted_at.insert(key(1.0, 1.0, 10, 1), stale);
+
+        assert_eq!(obf_cache.purge_older_than(day), 1);
+        assert_eq!(obf_cache.peek(1.0, 1.0, 10, 1), None);
+        assert_eq!(obf_cache.peek(1.0, 1.0, 20, 1), Some(20));
+        assert_eq!(obf_cache.peek(1.0, 1.0, 30, 1), Some(30));
+        assert_eq!(obf_cache.recency.len(), 2);
+        assert_eq!(obf_cache.purge_older_than(day), 0);
+    }
+
+    #[test]
+    fn test_get_from_cache_or_privatize_sensitivity() {
+        // the second parameter is the sensitivity of the query, as in `privatize`, and part of
+        // the cache key
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut rng_privatize = StdRng::seed_from_u64(42);
+        let mut obf_cache = ObfCache::new();
+        let result = get_from_cache_or_privatize(
+            27,
+            10.0,
+            0.5,
+            1,
+            Some(&mut obf_cache),
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            privatize(27, 10.0, 0.5, 1, &mut rng_privatize).unwrap()
+        );
+        assert_eq!(obf_cache.peek(10.0, 0.5, 27, 1), Some(result));
+    }
+
+    #[test]
+    fn test_obf_cache_keyed_by_exact_sensitivity() {
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::new();
+        for sensitivity in [1.2, 1.4, 1.2] {
+            get_from_cache_or_privatize(
+                10,
+                sensitivity,
+                1.0,
+                1,
+                Some(&mut obf_cache),
+                true,
+                ObfuscateBelow10Mode::Obfuscate,
+                1,
+                &mut rng,
+            )
+            .unwrap();
+        }
+        assert_eq!(obf_cache.len(), 2);
+        assert_eq!(obf_cache.hits(), 1);
+        assert!(obf_cache.peek(1.2, 1.0, 10, 1).is_some());
+        assert!(obf_cache.peek(1.4, 1.0, 10, 1).is_some());
+        assert_eq!(obf_cache.peek(1.0, 1.0, 10, 1), None);
+    }
+
+    #[test]
+    fn test_privatize_count_with_suppression() {
+        let mut rng = rand::thread_rng();
+        let mut obfuscate = |value, obfuscate_zero, mode| {
+            privatize_count_with_suppression(
+                value,
+                1.0,
+                1.0,
+                1,
+                None,
+                obfuscate_zero,
+                mode,
+                1,
+                &mut rng,
+            )
+            .unwrap()
+        };
+        assert_eq!(
+            obfuscate(0, false, ObfuscateBelow10Mode::Ten),
+            ObfuscationResult::SuppressedZero
+        );
+        assert_eq!(
+            obfuscate(5, true, ObfuscateBelow10Mode::Zero),
+            ObfuscationResult::SuppressedBelowThreshold
+        );
+        assert_eq!(
+            obfuscate(5, true, ObfuscateBelow10Mode::Ten),
+            ObfuscationResult::ClampedToTen
+        );
+        assert!(matches!(
+            obfuscate(5, true, ObfuscateBelow10Mode::Obfuscate),
+            ObfuscationResult::Obfuscated(_)
+        ));
+        assert!(matches!(
+            obfuscate(0, true, ObfuscateBelow10Mode::Obfuscate),
+            ObfuscationResult::Obfuscated(_)
+        ));
+        assert!(matches!(
+            obfuscate(27, false, ObfuscateBelow10Mode::Zero),
+            ObfuscationResult::Obfuscated(_)
+        ));
+        assert_eq!(ObfuscationResult::ClampedToTen.value(), 10);
+        assert_eq!(ObfuscationResult::Obfuscated(27).value(), 27);
+    }
+
+    #[test]
+    fn test_obfuscation_result_display() {
+        assert_eq!(
+            ObfuscationResult::SuppressedBelowThreshold.to_string(),
+            "<10"
+        );
+        assert_eq!(ObfuscationResult::SuppressedZero.to_string(), "0");
+        assert_eq!(ObfuscationResult::ClampedToTen.to_string(), "10");
+        assert_eq!(ObfuscationResult::Obfuscated(27).to_string(), "27");
+
+        assert_eq!(
+            ObfuscationResult::SuppressedBelowThreshold.to_display_string(5),
+            "<5"
+        );
+        assert_eq!(ObfuscationResult::SuppressedZero.to_display_string(5), "0");
+        assert_eq!(ObfuscationResult::ClampedToTen.to_display_string(5), "10");
+        assert_eq!(
+            ObfuscationResult::Obfuscated(1200).to_display_string(5),
+            "1200"
+        );
+    }
+
+    #[test]
+    fn test_privatize_count_with_suppression_matches_get_from_cache_or_privatize() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut rng_value = StdRng::seed_from_u64(42);
+        for value in [0, 5, 27, 1000] {
+            let result = privatize_count_with_suppression(
+                value,
+                1.0,
+                0.5,
+                1,
+                None,
+                false,
+                ObfuscateBelow10Mode::Ten,
+                1,
+                &mut rng,
+            )
+            .unwrap();
+            let expected = get_from_cache_or_privatize(
+                value,
+                1.0,
+                0.5,
+                1,
+                None,
+                false,
+                ObfuscateBelow10Mode::Ten,
+                1,
+                &mut rng_value,
+            )
+            .unwrap();
+            assert_eq!(result.value(), expected);
+        }
+    }
+
+    #[test]
+    fn test_obf_cache_keyed_by_epsilon() {
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::new();
+        let first = get_from_cache_or_privatize(
+            10,
+            1.0,
+            1.0,
+            1,
+            Some(&mut obf_cache),
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        )
+        .unwrap();
+        let second = get_from_cache_or_privatize(
+            10,
+            1.0,
+            0.1,
+            1,
+            Some(&mut obf_cache),
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(obf_cache.len(), 2);
+        assert_eq!(obf_cache.peek(1.0, 1.0, 10, 1), Some(first));
+        assert_eq!(obf_cache.peek(1.0, 0.1, 10, 1), Some(second));
+    }
+
+    #[test]
+    fn test_obf_cache_peek() {
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::new();
+        assert_eq!(obf_cache.peek(1.0, 1.0, 10, 1), None);
+        assert!(obf_cache.is_empty());
+
+        let result = get_from_cache_or_privatize(
+            10,
+            1.0,
+            1.0,
+            1,
+            Some(&mut obf_cache),
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(obf_cache.peek(1.0, 1.0, 10, 1), Some(result));
+        assert_eq!(obf_cache.peek(1.0, 1.0, 10, 2), None);
+    }
+
+    #[test]
+    fn test_obf_cache_stats() {
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::new();
+        assert_eq!(obf_cache.hit_rate(), 0.0);
+
+        get_from_cache_or_privatize(
+            10,
+            1.0,
+            1.0,
+            1,
+            Some(&mut obf_cache),
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!((obf_cache.hits(), obf_cache.misses()), (0, 1));
+
+        get_from_cache_or_privatize(
+            10,
+            1.0,
+            1.0,
+            1,
+            Some(&mut obf_cache),
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!((obf_cache.hits(), obf_cache.misses()), (1, 1));
+
+        for _ in 0..2 {
+            get_from_cache_or_privatize(
+                10,
+                1.0,
+                1.0,
+                1,
+                Some(&mut obf_cache),
+                true,
+                ObfuscateBelow10Mode::Obfuscate,
+                1,
+                &mut rng,
+            )
+            .unwrap();
+        }
+        assert_eq!((obf_cache.hits(), obf_cache.misses()), (3, 1));
+        assert_eq!(obf_cache.hit_rate(), 0.75);
+
+        obf_cache.reset_stats();
+        assert_eq!((obf_cache.hits(), obf_cache.misses()), (0, 0));
+        assert_eq!(obf_cache.len(), 1);
+    }
+
+    #[test]
+    fn test_obf_cache_with_capacity_zero() {
+        assert!(matches!(
+            ObfCache::with_capacity(0),
+            Err(LaplaceError::InvalidCacheCapacity)
+        ));
+        let obf_cache = ObfCache::with_capacity(1).unwrap();
+        assert_eq!(obf_cache.max_entries(), Some(1));
+    }
+
+    #[test]
+    fn test_obf_cache_with_capacity_evicts_oldest() {
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::with_capacity(2).unwrap();
+        assert_eq!(obf_cache.max_entries(), Some(2));
+
+        for value in [10, 20, 30] {
+            get_from_cache_or_privatize(
+                value,
+                1.0,
+                1.0,
+                1,
+                Some(&mut obf_cache),
+                true,
+                ObfuscateBelow10Mode::Obfuscate,
+                1,
+                &mut rng,
+            )
+            .unwrap();
+        }
+        assert_eq!(obf_cache.cache.len(), 2);
+        assert!(!obf_cache.cache.contains_key(&key(1.0, 1.0, 10, 1)));
+        assert!(obf_cache.cache.contains_key(&key(1.0, 1.0, 20, 1)));
+        assert!(obf_cache.cache.contains_key(&key(1.0, 1.0, 30, 1)));
+    }
+
+    #[test]
+    fn test_obf_cache_with_capacity_evicts_least_recently_used() {
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::with_capacity(2).unwrap();
+
+        for value in [10, 20, 10, 30] {
+            get_from_cache_or_privatize(
+                value,
+                1.0,
+                1.0,
+                1,
+                Some(&mut obf_cache),
+                true,
+                ObfuscateBelow10Mode::Obfuscate,
+                1,
+                &mut rng,
+            )
+            .unwrap();
+        }
+        assert_eq!(obf_cache.cache.len(), 2);
+        assert!(obf_cache.cache.contains_key(&key(1.0, 1.0, 10, 1)));
+        assert!(!obf_cache.cache.contains_key(&key(1.0, 1.0, 20, 1)));
+        assert!(obf_cache.cache.contains_key(&key(1.0, 1.0, 30, 1)));
+    }
+
+    #[test]
+    fn test_obf_cache_new_unbounded() {
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::new();
+        assert_eq!(obf_cache.max_entries(), None);
+
+        for value in 10..110 {
+            get_from_cache_or_privatize(
+                value,
+                1.0,
+                1.0,
+                1,
+                Some(&mut obf_cache),
+                true,
+                ObfuscateBelow10Mode::Obfuscate,
+                1,
+                &mut rng,
+            )
+            .unwrap();
+        }
+        assert_eq!(obf_cache.cache.len(), 100);
+    }
+
+    #[test]
+    fn test_privatize_batch_with_obf_cache() {
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::new();
+        let values = [20, 30, 20, 20, 30];
+        let bins = [1, 1, 1, 2, 1];
+
+        let result = privatize_batch(
+            &values,
+            1.0,
+            0.1,
+            &bins,
+            Some(&mut obf_cache),
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(result.len(), values.len());
+        assert_eq!(obf_cache.cache.len(), 3);
+        assert_eq!(result[0], result[2]);
+        assert_eq!(result[1], result[4]);
+        assert_eq!(result[0], obf_cache.cache[&key(1.0, 0.1, 20, 1)]);
+        assert_eq!(result[3], obf_cache.cache[&key(1.0, 0.1, 20, 2)]);
+    }
+
+    #[test]
+    fn test_privatize_histogram_with_obf_cache() {
+        let mut rng = rand::thread_rng();
+        let mut obf_cache = ObfCache::new();
+        let histogram = HashMap::from([(1, 20), (2, 20), (3, 35)]);
+
+        let first = privatize_histogram(
+            &histogram,
+            1.0,
+            0.1,
+            Some(&mut obf_cache),
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        )
+        .unwrap();
+        let mut bins: Vec<Bin> = first.keys().copied().collect();
+        bins.sort_unstable();
+        assert_eq!(bins, [1, 2, 3]);
+        assert_eq!(obf_cache.len(), 3);
+        assert_eq!(first[&1], obf_cache.cache[&key(1.0, 0.1, 20, 1)]);
+        assert_eq!(first[&2], obf_cache.cache[&key(1.0, 0.1, 20, 2)]);
+
+        let second = privatize_histogram(
+            &histogram,
+            1.0,
+            0.1,
+            Some(&mut obf_cache),
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_privatize_histogram_l1_noise_magnitude() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let values = vec![1000; 10_000];
+        let mean_absolute_error = |obfuscated: &[u64]| {
+            obfuscated
+                .iter()
+                .map(|&value| (value as f64 - 1000.0).abs())
+                .sum::<f64>()
+                / obfuscated.len() as f64
+        };
+
+        let single: Vec<u64> = values
+            .iter()
+            .map(|&value| privatize(value, 1.0, 1.0, 1, &mut rng).unwrap())
+            .collect();
+        let l1 = privatize_histogram_l1(&values, 5.0, 1.0, &mut rng).unwrap();
+        assert_eq!(l1.len(), values.len());
+        let ratio = mean_absolute_error(&l1) / mean_absolute_error(&single);
+        assert!((4.0..6.0).contains(&ratio), "ratio {ratio}");
+    }
+
+    #[test]
+    fn test_privatize_histogram_l1_err() {
+        let mut rng = rand::thread_rng();
+        let result = privatize_histogram_l1(&[10, 20], 1.0, 0.0, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::InvalidEpsilon(_))));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_privatize_batch_parallel_matches_serial() {
+        let values: Vec<u64> = (0..10_000).collect();
+        let chunk_size = 256;
+        let seed = 1234;
+
+        let parallel = privatize_batch_parallel(&values, 1.0, 0.5, 10, seed, chunk_size).unwrap();
+
+        let mut serial = Vec::with_capacity(values.len());
+        for (index, chunk) in values.chunks(chunk_size).enumerate() {
+            let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(seed);
+            rng.set_stream(index as u64);
+            for &value in chunk {
+                serial.push(privatize(value, 1.0, 0.5, 10, &mut rng).unwrap());
+            }
+        }
+        assert_eq!(parallel, serial);
+
+        // The second chunk of a seed must not reuse the first chunk of the next seed.
+        let next_seed =
+            privatize_batch_parallel(&values, 1.0, 0.5, 10, seed + 1, chunk_size).unwrap();
+        assert_ne!(
+            parallel[chunk_size..2 * chunk_size],
+            next_seed[..chunk_size]
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_privatize_batch_parallel_invalid_params() {
+        let result = privatize_batch_parallel(&[], 1.0, 0.0, 10, 42, 256);
+        assert!(matches!(result, Err(LaplaceError::InvalidEpsilon(_))));
+        let result = privatize_batch_parallel(&[1, 2], 1.0, 0.5, 0, 42, 256);
+        assert!(matches!(
+            result,
+            Err(LaplaceError::InvalidArgRoundingStepZero)
+        ));
+    }
+
+    #[test]
+    fn test_rng_for_bin() {
+        use rand::RngCore;
+
+        assert_eq!(rng_for_bin(42, 3).next_u64(), rng_for_bin(42, 3).next_u64());
+        assert_ne!(rng_for_bin(42, 3).next_u64(), rng_for_bin(42, 4).next_u64());
+        assert_ne!(rng_for_bin(42, 3).next_u64(), rng_for_bin(43, 3).next_u64());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_privatize_bins_parallel_independent_of_order_and_threads() {
+        let values: Vec<u64> = (0..1000).map(|value| value * 7).collect();
+        let bins: Vec<Bin> = (0..1000).collect();
+        let run = |threads: usize, values: &[u64], bins: &[Bin]| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            let obfuscated = pool
+                .install(|| privatize_bins_parallel(values, bins, 1.0, 0.5, 10, 1234))
+                .unwrap();
+            bins.iter()
+                .copied()
+                .zip(obfuscated)
+                .collect::<HashMap<Bin, u64>>()
+        };
+
+        let expected = run(1, &values, &bins);
+        assert_eq!(run(4, &values, &bins), expected);
+        let reversed_values: Vec<u64> = values.iter().rev().copied().collect();
+        let reversed_bins: Vec<Bin> = bins.iter().rev().copied().collect();
+        assert_eq!(run(3, &reversed_values, &reversed_bins), expected);
+
+        let mut rng = rng_for_bin(1234, 10);
+        assert_eq!(
+            expected[&10],
+            privatize(70, 1.0, 0.5, 10, &mut rng).unwrap()
+        );
+        assert!(matches!(
+            privatize_bins_parallel(&values, &bins[1..], 1.0, 0.5, 10, 1234),
+            Err(LaplaceError::LengthMismatch(1000, 999))
+        ));
+    }
+
+    #[test]
+    fn test_privatize_batch_per_bin_noise_magnitude() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let value = 1000;
+        let runs = 1000;
+        let (mut deviation_precise, mut deviation_noisy) = (0, 0);
+        for _ in 0..runs {
+            let result =
+                privatize_batch_per_bin(&[value, value], &[1.0, 1.0], &[10.0, 0.01], 1, &mut rng)
+                    .unwrap();
+            deviation_precise += result[0].abs_diff(value);
+            deviation_noisy += result[1].abs_diff(value);
+        }
+        assert!(deviation_precise < runs);
+        assert!(deviation_noisy > 50 * runs);
+    }
+
+    #[test]
+    fn test_privatize_batch_per_bin_matches_privatize() {
+        let values = [5, 27, 27, 1000, 3];
+        let sensitivities = [1.0, 1.0, 2.0, 2.0, 1.0];
+        let epsilons = [0.5, 0.5, 0.5, 0.5, 0.1];
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut rng_expected = StdRng::seed_from_u64(5);
+        let result =
+            privatize_batch_per_bin(&values, &sensitivities, &epsilons, 5, &mut rng).unwrap();
+        for (index, &value) in values.iter().enumerate() {
+            let expected = privatize(
+                value,
+                sensitivities[index],
+                epsilons[index],
+                5,
+                &mut rng_expected,
+            )
+            .unwrap();
+            assert_eq!(result[index], expected);
+        }
+
+        let result = privatize_batch_per_bin(&[1, 2], &[1.0, 1.0], &[0.5, 0.0], 1, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::InvalidEpsilon(_))));
+    }
+
+    #[test]
+    fn test_privatize_batch_per_bin_length_mismatch() {
+        let mut rng = rand::thread_rng();
+        let result = privatize_batch_per_bin(&[10, 20], &[1.0], &[1.0, 1.0], 1, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::LengthMismatch(2, 1))));
+        let result = privatize_batch_per_bin(&[10, 20], &[1.0, 1.0], &[1.0; 3], 1, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::LengthMismatch(2, 3))));
+    }
+
+    #[cfg(feature = "bulk")]
+    #[test]
+    fn test_sample_laplace_bulk_matches_single() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let len = 20_000;
+        let mut single: Vec<f64> = (0..len)
+            .map(|_| sample_laplace(0.0, 2.0, &mut rng))
+            .collect();
+        let mut bulk = sample_laplace_bulk(2.0, len, &mut rng);
+        assert!(bulk.iter().all(|noise| noise.is_finite()));
+        single.sort_by(f64::total_cmp);
+        bulk.sort_by(f64::total_cmp);
+
+        // two-sample Kolmogorov-Smirnov statistic, the largest distance of the empirical CDFs
+        let (mut i, mut j, mut statistic) = (0, 0, 0.0f64);
+        while i < len && j < len {
+            if single[i] <= bulk[j] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+            statistic = statistic.max((i as f64 - j as f64).abs() / len as f64);
+        }
+        // critical value at a significance level of 0.001
+        assert!(statistic < 1.95 * (2.0 / len as f64).sqrt());
+    }
+
+    #[cfg(feature = "bulk")]
+    #[test]
+    fn test_privatize_bulk() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let values = [0, 10, 1000, 25_000];
+        let obfuscated = privatize_bulk(&values, 1.0, 0.1, 10, &mut rng).unwrap();
+        assert_eq!(obfuscated.len(), values.len());
+        assert!(obfuscated.iter().all(|value| value.is_multiple_of(10)));
+
+        let noiseless = privatize_bulk(&values, 1.0, f64::INFINITY, 1, &mut rng).unwrap();
+        assert_eq!(noiseless, values);
+
+        let result = privatize_bulk(&values, 1.0, 0.1, 0, &mut rng);
+        assert!(matches!(
+            result,
+            Err(LaplaceError::InvalidArgRoundingStepZero)
+        ));
+        let result = privatize_bulk(&[MAX_EXACT_VALUE + 1], 1.0, 0.1, 1, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::PrecisionLoss(_))));
+        assert!(privatize_bulk(&[], 1.0, 0.1, 1, &mut rng)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_privatize_batch_length_mismatch() {
+        let mut rng = rand::thread_rng();
+        let result = privatize_batch(
+            &[10, 20],
+            1.0,
+            1.0,
+            &[1],
+            None,
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        );
+        assert!(matches!(result, Err(LaplaceError::LengthMismatch(2, 1))));
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_round_parametric_multiple_of_step(value in proptest::num::f64::ANY, step in 1usize..1000) {
+            match round_parametric(value, step) {
+                Ok(rounded) => proptest::prop_assert_eq!(rounded % step as u64, 0),
+                Err(_) => proptest::prop_assert!(!value.is_finite()),
+            }
+        }
+
+        #[test]
+        fn prop_round_parametric_within_step(value in 0.0..1e12f64, step in 1usize..1000) {
+            let rounded = round_parametric(value, step).unwrap();
+            proptest::prop_assert!((rounded as f64 - value).abs() <= step as f64);
+        }
+
+        #[test]
+        fn prop_round_parametric_negative_is_zero(value in -1e30..0.0f64, step in 1usize..1000) {
+            proptest::prop_assert_eq!(round_parametric(value, step).unwrap(), 0);
+        }
+
+        #[test]
+        fn prop_privatize_small_counts_dont_wrap(value in 0u64..100, epsilon in 0.1..10.0f64, seed: u64) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let result = privatize(value, 1.0, epsilon, 1, &mut rng).unwrap();
+            proptest::prop_assert!(result < 1_000_000);
+            let result = privatize_clamped(value, 1.0, epsilon, 1, &mut rng).unwrap();
+            proptest::prop_assert!(result < 1_000_000);
+        }
+    }
 }
@@ -0,0 +1,270 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use std::cell::RefCell;
+use std::fmt;
+
+use crate::config::DiffPrivConfig;
+use crate::errors::LaplaceError;
+#[allow(deprecated)]
+use crate::{get_from_cache_or_privatize, privatize, Bin, ObfCache, ObfuscateBelow10Mode};
+
+/// Obfuscates values with a seeded random generator it owns, so that an obfuscation run can be
+/// reproduced exactly, e.g. for auditing and regression testing. Given the same seed and the
+/// same sequence of calls, the same obfuscated values are returned.
+///
+/// Unlike `rand::thread_rng`, the obfuscator doesn't rely on thread-local state, so it can
+/// also be used on WASM, seeded with `from_entropy` (requires the `js` feature on
+/// wasm32-unknown-unknown).
+pub struct Obfuscator {
+    rng: ChaCha20Rng,
+}
+
+/// Omits the state of the random generator, which would reveal the noise.
+impl fmt::Debug for Obfuscator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Obfuscator").finish_non_exhaustive()
+    }
+}
+
+impl Obfuscator {
+    /// Creates an obfuscator with a random generator seeded from the given 32 bytes.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Obfuscator {
+            rng: ChaCha20Rng::from_seed(seed),
+        }
+    }
+
+    /// Creates an obfuscator with a random generator seeded from the given number.
+    pub fn seed_from_u64(seed: u64) -> Self {
+        Obfuscator {
+            rng: ChaCha20Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Creates an obfuscator with a random generator seeded from the operating system's, or on
+    /// WASM the browser's, source of randomness.
+    pub fn from_entropy() -> Self {
+        Obfuscator {
+            rng: ChaCha20Rng::from_entropy(),
+        }
+    }
+
+    /// The random generator of the obfuscator, e.g. to call other obfuscation functions.
+    pub fn rng(&mut self) -> &mut ChaCha20Rng {
+        &mut self.rng
+    }
+
+    /// Obfuscates the given value with `get_from_cache_or_privatize` using the random
+    /// generator of the obfuscator.
+    ///
+    /// # Arguments
+    ///
+    /// * value - The input value to be obfuscated.
+    /// * sensitivity - Sensitivity of query.
+    /// * epsilon - Privacy budget parameter.
+    /// * bin - The bin that the value belongs to.
+    /// * obf_cache_option - An option that represents the obfuscation cache.
+    /// * obfuscate_zero - A flag indicating whether zero counts should be obfuscated.
+    /// * below_10_obfuscation_mode: 0 - return 0, 1 - return 10, 2 - obfuscate using Laplace distribution and rounding
+    /// * rounding_step - The granularity of the rounding.
+    ///
+    /// # Returns
+    ///
+    /// The obfuscated value, rounded to the nearest multiple of the rounding_step, or an error
+    /// if the obfuscation failed.
+    #[allow(clippy::too_many_arguments, deprecated)]
+    pub fn obfuscate(
+        &mut self,
+        value: u64,
+        sensitivity: f64,
+        epsilon: f64,
+        bin: Bin,
+        obf_cache_option: Option<&mut ObfCache>,
+        obfuscate_zero: bool,
+        obfuscate_below_10_mode: ObfuscateBelow10Mode,
+        rounding_step: usize,
+    ) -> Result<u64, LaplaceError> {
+        get_from_cache_or_privatize(
+            value,
+            sensitivity,
+            epsilon,
+            bin,
+            obf_cache_option,
+            obfuscate_zero,
+            obfuscate_below_10_mode,
+            rounding_step,
+            &mut self.rng,
+        )
+    }
+
+    /// Obfuscates the given value with the parameters of the given config using the random
+    /// generator of the obfuscator, see `DiffPrivConfig::privatize`.
+    ///
+    /// # Arguments
+    ///
+    /// * value - The input value to be obfuscated.
+    /// * config - The parameters of the obfuscation.
+    /// * bin - The bin that the value belongs to.
+    /// * obf_cache_option - An option that represents the obfuscation cache.
+    ///
+    /// # Returns
+    ///
+    /// The obfuscated value, or an error if the obfuscation failed.
+    pub fn obfuscate_with_config(
+        &mut self,
+        value: u64,
+        config: &DiffPrivConfig,
+        bin: Bin,
+        obf_cache_option: Option<&mut ObfCache>,
+    ) -> Result<u64, LaplaceError> {
+        config.privatize(value, bin, obf_cache_option, &mut self.rng)
+    }
+
+    /// Obfuscates the given value with `privatize` using the random generator of the
+    /// obfuscator.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Clear value to permute.
+    /// * `sensitivity` - Sensitivity of query.
+    /// * `epsilon` - Privacy budget parameter.
+    /// * `rounding_step` - Rounding to the given number is performed.
+    ///
+    /// # Returns
+    ///
+    /// The obfuscated value, or an error if the obfuscation failed.
+    pub fn privatize(
+        &mut self,
+        value: u64,
+        sensitivity: f64,
+        epsilon: f64,
+        rounding_step: usize,
+    ) -> Result<u64, LaplaceError> {
+        privatize(value, sensitivity, epsilon, rounding_step, &mut self.rng)
+    }
+}
+
+thread_local! {
+    static OBFUSCATOR: RefCell<Obfuscator> = RefCell::new(Obfuscator::from_entropy());
+}
+
+/// Calls the given function with the obfuscator of the current thread, which is seeded from
+/// the operating system's source of randomness on the first call on the thread. This avoids
+/// seeding a random generator per request, e.g. in the handlers of a web server.
+///
+/// # Panics
+///
+/// Panics if called again from within the given function.
+pub fn with_obfuscator<T, F: FnOnce(&mut Obfuscator) -> T>(f: F) -> T {
+    OBFUSCATOR.with(|obfuscator| f(&mut obfuscator.borrow_mut()))
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod test {
+    use super::*;
+
+    fn obfuscate_sequence(obfuscator: &mut Obfuscator) -> Vec<u64> {
+        (10..60)
+            .map(|value| {
+                obfuscator
+                    .obfuscate(
+                        value,
+                        1.0,
+                        0.1,
+                        1,
+                        None,
+                        true,
+                        ObfuscateBelow10Mode::Obfuscate,
+                        1,
+                    )
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut first = Obfuscator::seed_from_u64(42);
+        let mut second = Obfuscator::seed_from_u64(42);
+        assert_eq!(
+            obfuscate_sequence(&mut first),
+            obfuscate_sequence(&mut second)
+        );
+
+        let mut first = Obfuscator::from_seed([7; 32]);
+        let mut second = Obfuscator::from_seed([7; 32]);
+        assert_eq!(
+            first.privatize(27, 1.0, 0.1, 1).unwrap(),
+            second.privatize(27, 1.0, 0.1, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_obfuscate_with_config_reproducible() {
+        let config = DiffPrivConfig::new().epsilon(0.1).rounding_step(5);
+        let obfuscate = |seed| {
+            let mut obfuscator = Obfuscator::seed_from_u64(seed);
+            let mut obf_cache = ObfCache::new();
+            (10..60)
+                .map(|value| {
+                    obfuscator
+                        .obfuscate_with_config(value, &config, 1, Some(&mut obf_cache))
+                        .unwrap()
+                })
+                .collect::<Vec<u64>>()
+        };
+        let obfuscated = obfuscate(42);
+        assert!(obfuscated.iter().all(|value| value % 5 == 0));
+        assert_eq!(obfuscated, obfuscate(42));
+        assert_ne!(obfuscated, obfuscate(43));
+    }
+
+    #[test]
+    fn test_different_seeds_diverge() {
+        let mut first = Obfuscator::seed_from_u64(42);
+        let mut second = Obfuscator::seed_from_u64(43);
+        assert_ne!(
+            obfuscate_sequence(&mut first),
+            obfuscate_sequence(&mut second)
+        );
+    }
+
+    #[test]
+    fn test_debug_omits_rng() {
+        let obfuscator = Obfuscator::seed_from_u64(42);
+        assert_eq!(format!("{:?}", obfuscator), "Obfuscator { .. }");
+    }
+
+    #[test]
+    fn test_with_obfuscator_reuses_instance() {
+        let (first, first_word_pos) = with_obfuscator(|obfuscator| {
+            obfuscator.privatize(27, 1.0, 0.1, 1).unwrap();
+            (
+                obfuscator as *const Obfuscator,
+                obfuscator.rng().get_word_pos(),
+            )
+        });
+        let (second, second_word_pos) = with_obfuscator(|obfuscator| {
+            obfuscator.privatize(27, 1.0, 0.1, 1).unwrap();
+            (
+                obfuscator as *const Obfuscator,
+                obfuscator.rng().get_word_pos(),
+            )
+        });
+        assert_eq!(first, second);
+        assert!(second_word_pos > first_word_pos);
+
+        let other_thread =
+            std::thread::spawn(|| with_obfuscator(|obfuscator| obfuscator.rng().get_word_pos()))
+                .join()
+                .unwrap();
+        assert_eq!(other_thread, 0);
+    }
+
+    #[test]
+    fn test_from_entropy() {
+        let mut obfuscator = Obfuscator::from_entropy();
+        assert!(obfuscator.privatize(27, 1.0, 0.1, 1).is_ok());
+    }
+}
@@ -0,0 +1,132 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+/// Post-processes independently noised counts, e.g. of the bins of a histogram, into
+/// consistent counts: non-negative integers that, if `total` is given, add up to the rounded
+/// total. The counts are projected onto the non-negative counts with the given total in the
+/// least-squares sense, by subtracting the same offset from every count and clamping at 0,
+/// and then rounded with the largest remainder method so that the total is preserved.
+/// Without a total, negative counts are clamped to 0 and the counts rounded to the nearest
+/// integer. Non-finite counts are treated as 0.
+///
+/// As post-processing of obfuscated counts, this doesn't affect the privacy guarantee.
+///
+/// # Arguments
+///
+/// * `noisy` - The noised counts.
+/// * `total` - The total the counts should add up to, e.g. a separately noised total.
+///
+/// # Returns
+///
+/// The consistent counts, in the order of the noised counts.
+pub fn make_consistent(noisy: &[f64], total: Option<f64>) -> Vec<u64> {
+    let noisy: Vec<f64> = noisy
+        .iter()
+        .map(|&count| if count.is_finite() { count } else { 0.0 })
+        .collect();
+    let Some(total) = total else {
+        return noisy
+            .iter()
+            .map(|&count| count.max(0.0).round() as u64)
+            .collect();
+    };
+    let total = if total.is_finite() {
+        total.max(0.0).round()
+    } else {
+        0.0
+    };
+    if total == 0.0 {
+        return noisy.iter().map(|_| 0).collect();
+    }
+
+    // the offset of the projection onto the non-negative counts adding up to the total
+    let mut sorted = noisy.clone();
+    sorted.sort_unstable_by(|a, b| b.total_cmp(a));
+    let mut sum = 0.0;
+    let mut offset = 0.0;
+    for (index, &count) in sorted.iter().enumerate() {
+        sum += count;
+        let candidate = (sum - total) / (index + 1) as f64;
+        if count - candidate > 0.0 {
+            offset = candidate;
+        }
+    }
+    let projected: Vec<f64> = noisy
+        .iter()
+        .map(|&count| (count - offset).max(0.0))
+        .collect();
+
+    // round down, then round up the counts with the largest remainders to reach the total
+    let mut consistent: Vec<u64> = projected
+        .iter()
+        .map(|&count| count.floor() as u64)
+        .collect();
+    let rounded_total: u64 = consistent.iter().sum();
+    let missing = (total as u64).saturating_sub(rounded_total) as usize;
+    let mut by_remainder: Vec<usize> = (0..projected.len()).collect();
+    by_remainder.sort_by(|&a, &b| {
+        let remainder = |index: usize| projected[index] - projected[index].floor();
+        remainder(b).total_cmp(&remainder(a))
+    });
+    for &index in by_remainder.iter().cycle().take(missing) {
+        consistent[index] += 1;
+    }
+    consistent
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::privatize_f64;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_make_consistent_without_total() {
+        assert_eq!(
+            make_consistent(&[-3.2, 0.4, 10.6, 7.0, f64::NAN], None),
+            [0, 0, 11, 7, 0]
+        );
+        assert!(make_consistent(&[], None).is_empty());
+    }
+
+    #[test]
+    fn test_make_consistent_with_total() {
+        // subtracting 1 from every count and clamping at 0 gives 9, 6 and 0, which add up to 15
+        assert_eq!(make_consistent(&[10.0, 7.0, -2.0], Some(15.0)), [9, 6, 0]);
+        // adding 1 to every count gives 2, 3 and 4
+        assert_eq!(make_consistent(&[1.0, 2.0, 3.0], Some(9.0)), [2, 3, 4]);
+        // the remainder of 1 goes to the count with the largest fractional part
+        assert_eq!(make_consistent(&[1.2, 1.7, 1.1], Some(4.0)), [1, 2, 1]);
+        assert_eq!(make_consistent(&[5.0, -1.0], Some(-3.0)), [0, 0]);
+        assert_eq!(make_consistent(&[-5.0, -1.0], Some(4.0)), [0, 4]);
+        assert!(make_consistent(&[], Some(10.0)).is_empty());
+    }
+
+    #[test]
+    fn test_make_consistent_noised_histogram() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let counts = [0.0, 2.0, 5.0, 40.0, 120.0, 1.0, 0.0, 33.0];
+        let true_total: f64 = counts.iter().sum();
+        for _ in 0..100 {
+            let noisy: Vec<f64> = counts
+                .iter()
+                .map(|&count| privatize_f64(count, 1.0, 0.5, &mut rng).unwrap())
+                .collect();
+            let total = privatize_f64(true_total, 1.0, 0.5, &mut rng).unwrap();
+            let consistent = make_consistent(&noisy, Some(total));
+            assert_eq!(consistent.iter().sum::<u64>(), total.round() as u64);
+
+            let error = |consistent: &[u64]| {
+                counts
+                    .iter()
+                    .zip(consistent)
+                    .map(|(&count, &consistent)| (count - consistent as f64).abs())
+                    .sum::<f64>()
+            };
+            assert!(error(&consistent) < 4.0 * error(&make_consistent(&noisy, None)) + 20.0);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+//! Re-exports the types, traits and functions needed for typical use.
+//!
+//! ```
+//! use laplace_rs::prelude::*;
+//!
+//! let mut obf_cache = ObfCache::new();
+//! let mut rng = rand::thread_rng();
+//! let config = DiffPrivConfig::new()
+//!     .epsilon(0.1)
+//!     .rounding_step(10)
+//!     .small_count_policy(SmallCountPolicy {
+//!         threshold: 10,
+//!         mode: SuppressionMode::Clamp(10),
+//!     });
+//! let obfuscated = config.privatize(27, 1, Some(&mut obf_cache), &mut rng)?;
+//! assert_eq!(obfuscated % 10, 0);
+//!
+//! let mut obfuscator = Obfuscator::seed_from_u64(42);
+//! obfuscator.obfuscate_with_config(27, &config, 1, Some(&mut obf_cache))?;
+//!
+//! let mechanism = LaplaceMechanism::new(1.0, 0.1);
+//! mechanism.perturb(27.0, &mut rng)?;
+//! # Ok::<(), LaplaceError>(())
+//! ```
+
+pub use crate::budget::BudgetAccountant;
+#[cfg(feature = "std")]
+pub use crate::config::DiffPrivConfig;
+pub use crate::errors::LaplaceError;
+pub use crate::iter::PrivatizeExt;
+#[cfg(feature = "std")]
+pub use crate::mechanism::GaussianMechanism;
+pub use crate::mechanism::{IdentityMechanism, LaplaceMechanism, Mechanism, PrivacyGuarantee};
+#[cfg(feature = "std")]
+pub use crate::obfuscator::{with_obfuscator, Obfuscator};
+pub use crate::response::{randomized_response, randomized_response_k};
+#[cfg(feature = "std")]
+pub use crate::sync_cache::{get_from_sync_cache_or_privatize, SyncObfCache};
+#[cfg(feature = "std")]
+pub use crate::{
+    get_from_cache_or_privatize, privatize_batch, privatize_count_with_suppression,
+    privatize_histogram, MergePolicy, ObfCache,
+};
+#[allow(deprecated)]
+pub use crate::{
+    privatize, privatize_f64, validate_params, Bin, ObfuscateBelow10Mode, ObfuscationResult,
+    RoundingMode, RoundingOrder, SmallCountPolicy, SuppressionMode,
+};
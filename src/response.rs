@@ -0,0 +1,135 @@
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use rand::Rng;
+
+use crate::errors::LaplaceError;
+
+/// Privately reports a boolean attribute with randomized response, flipping the answer with
+/// probability `1 / (1 + exp(epsilon))`. With an infinite epsilon, the answer is never
+/// flipped, which disables privacy.
+///
+/// # Arguments
+///
+/// * `true_value` - The clear value to report.
+/// * `epsilon` - Privacy budget parameter.
+/// * `rng` - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The reported value, or an error if epsilon is invalid.
+pub fn randomized_response<R: Rng + ?Sized>(
+    true_value: bool,
+    epsilon: f64,
+    rng: &mut R,
+) -> Result<bool, LaplaceError> {
+    Ok(randomized_response_k(true_value as usize, 2, epsilon, rng)? == 1)
+}
+
+/// Privately reports a categorical attribute with `k` categories with k-ary randomized
+/// response. The true category is reported with probability
+/// `exp(epsilon) / (exp(epsilon) + k - 1)`, otherwise one of the other categories is reported
+/// uniformly at random. With an infinite epsilon, the true category is always reported.
+///
+/// # Arguments
+///
+/// * `value` - The clear category to report, in `0..k`.
+/// * `k` - The number of categories.
+/// * `epsilon` - Privacy budget parameter.
+/// * `rng` - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The reported category, or an error if the category or epsilon are invalid.
+pub fn randomized_response_k<R: Rng + ?Sized>(
+    value: usize,
+    k: usize,
+    epsilon: f64,
+    rng: &mut R,
+) -> Result<usize, LaplaceError> {
+    if value >= k {
+        return Err(LaplaceError::InvalidCategory(value, k));
+    }
+    if epsilon.is_nan() || epsilon <= 0.0 {
+        return Err(LaplaceError::InvalidEpsilon(epsilon));
+    }
+    // the probability of reporting another category, written so that an infinite epsilon
+    // gives 0 instead of NaN
+    let others = (k - 1) as f64 * (-epsilon).exp();
+    if rng.gen::<f64>() >= others / (1.0 + others) {
+        return Ok(value);
+    }
+    let other = rng.gen_range(0..k - 1);
+    Ok(if other >= value { other + 1 } else { other })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_randomized_response_flip_probability() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let n = 100_000;
+        for epsilon in [0.1, 1.0, 3.0] {
+            let flipped = (0..n)
+                .filter(|_| !randomized_response(true, epsilon, &mut rng).unwrap())
+                .count();
+            let expected = 1.0 / (1.0 + f64::exp(epsilon));
+            assert!((flipped as f64 / n as f64 - expected).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_randomized_response_infinite_epsilon() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10_000 {
+            assert!(randomized_response(true, f64::INFINITY, &mut rng).unwrap());
+            assert!(!randomized_response(false, f64::INFINITY, &mut rng).unwrap());
+            assert_eq!(
+                randomized_response_k(3, 5, f64::INFINITY, &mut rng).unwrap(),
+                3
+            );
+        }
+    }
+
+    #[test]
+    fn test_randomized_response_k_distribution() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let (n, k, epsilon) = (100_000, 4, 1.0);
+        let mut reported = [0; 4];
+        for _ in 0..n {
+            reported[randomized_response_k(2, k, epsilon, &mut rng).unwrap()] += 1;
+        }
+        let kept = f64::exp(epsilon) / (f64::exp(epsilon) + 3.0);
+        for (category, &count) in reported.iter().enumerate() {
+            let expected = if category == 2 {
+                kept
+            } else {
+                (1.0 - kept) / 3.0
+            };
+            assert!((count as f64 / n as f64 - expected).abs() < 0.01);
+        }
+        assert_eq!(randomized_response_k(0, 1, 0.1, &mut rng).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_randomized_response_invalid() {
+        let mut rng = rand::thread_rng();
+        for epsilon in [0.0, -1.0, f64::NAN] {
+            assert!(matches!(
+                randomized_response(true, epsilon, &mut rng),
+                Err(LaplaceError::InvalidEpsilon(_))
+            ));
+        }
+        assert!(matches!(
+            randomized_response_k(4, 4, 1.0, &mut rng),
+            Err(LaplaceError::InvalidCategory(4, 4))
+        ));
+        assert!(matches!(
+            randomized_response_k(0, 0, 1.0, &mut rng),
+            Err(LaplaceError::InvalidCategory(0, 0))
+        ));
+    }
+}
@@ -0,0 +1,313 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+use rand::Rng;
+
+use crate::errors::LaplaceError;
+use crate::mechanism::{LaplaceMechanism, Mechanism};
+use crate::validate_privacy_parameters;
+
+/// Privately selects a candidate with the exponential mechanism. The candidate at index `i`
+/// is selected with probability proportional to `exp(epsilon * scores[i] / (2 * sensitivity))`.
+///
+/// # Arguments
+///
+/// * `candidates` - The candidates to select from.
+/// * `scores` - The score of each candidate, higher scores are selected more often.
+/// * `sensitivity` - Sensitivity of the score function, must be positive.
+/// * `epsilon` - Privacy budget parameter.
+/// * `rng` - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The index of the selected candidate, or an error if the candidates are empty, the slice
+/// lengths don't match or the parameters are invalid.
+pub fn exponential_mechanism<T, R: Rng + ?Sized>(
+    candidates: &[T],
+    scores: &[f64],
+    sensitivity: f64,
+    epsilon: f64,
+    rng: &mut R,
+) -> Result<usize, LaplaceError> {
+    if candidates.len() != scores.len() {
+        return Err(LaplaceError::LengthMismatch(candidates.len(), scores.len()));
+    }
+    if candidates.is_empty() {
+        return Err(LaplaceError::EmptyInput);
+    }
+    validate_privacy_parameters(sensitivity, epsilon)?;
+    if sensitivity == 0.0 {
+        return Err(LaplaceError::InvalidSensitivity(sensitivity));
+    }
+    if let Some(&score) = scores.iter().find(|score| !score.is_finite()) {
+        return Err(LaplaceError::NonFiniteParameter {
+            name: "score",
+            value: score,
+        });
+    }
+
+    // shift by the maximum score so that the weights don't overflow
+    let max_score = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let weight = |score: f64| (epsilon * (score - max_score) / (2.0 * sensitivity)).exp();
+    let total: f64 = scores.iter().map(|&score| weight(score)).sum();
+    let mut threshold = rng.gen::<f64>() * total;
+    for (index, &score) in scores.iter().enumerate() {
+        threshold -= weight(score);
+        if threshold < 0.0 {
+            return Ok(index);
+        }
+    }
+    // only reachable through floating point rounding in the sum of the weights
+    Ok(scores.len() - 1)
+}
+
+/// Privately selects the largest count with Report Noisy Max, perturbing each count with the
+/// (epsilon, 0) laplacian mechanism and returning the index of the largest perturbed count.
+///
+/// # Arguments
+///
+/// * `counts` - The clear counts to select the largest from.
+/// * `sensitivity` - Sensitivity of each count.
+/// * `epsilon` - Privacy budget parameter.
+/// * `rng` - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The index of the largest perturbed count, or an error if the counts are empty or the
+/// perturbation failed.
+pub fn report_noisy_max<R: Rng + ?Sized>(
+    counts: &[u64],
+    sensitivity: f64,
+    epsilon: f64,
+    rng: &mut R,
+) -> Result<usize, LaplaceError> {
+    if counts.is_empty() {
+        return Err(LaplaceError::EmptyInput);
+    }
+    let mechanism = LaplaceMechanism::new(sensitivity, epsilon);
+    let mut max_index = 0;
+    let mut max_value = f64::NEG_INFINITY;
+    for (index, &count) in counts.iter().enumerate() {
+        let perturbed = mechanism.perturb(count as f64, rng)?;
+        if perturbed > max_value {
+            max_index = index;
+            max_value = perturbed;
+        }
+    }
+    Ok(max_index)
+}
+
+/// Privately estimates a quantile, e.g. the median, of the given values with the exponential
+/// mechanism over the range `[lower, upper]`. The values are clamped to the range and
+/// sorted, splitting the range into intervals between consecutive values. An interval is
+/// selected with probability proportional to its width times
+/// `exp(-epsilon * |rank - quantile * n| / 2)`, where `rank` is the number of values below the
+/// interval, and the estimate is sampled uniformly from the selected interval.
+///
+/// # Arguments
+///
+/// * `values` - The clear values to estimate the quantile of.
+/// * `quantile` - The quantile to estimate, in the interval [0, 1], e.g. 0.5 for the median.
+/// * `lower` - The lower bound of the range of the estimate.
+/// * `upper` - The upper bound of the range of the estimate, must exceed `lower`.
+/// * `epsilon` - Privacy budget parameter.
+/// * `rng` - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The estimated quantile in `[lower, upper]`, or an error if the quantile, the bounds, a
+/// value or epsilon are invalid.
+pub fn privatize_quantile<R: Rng + ?Sized>(
+    values: &[f64],
+    quantile: f64,
+    lower: f64,
+    upper: f64,
+    epsilon: f64,
+    rng: &mut R,
+) -> Result<f64, LaplaceError> {
+    if !(0.0..=1.0).contains(&quantile) {
+        return Err(LaplaceError::InvalidQuantile(quantile));
+    }
+    if !(lower.is_finite() && upper.is_finite() && lower < upper) {
+        return Err(LaplaceError::InvalidOutputBounds(lower, upper));
+    }
+    validate_privacy_parameters(1.0, epsilon)?;
+    if let Some(&value) = values.iter().find(|value| value.is_nan()) {
+        return Err(LaplaceError::NonFiniteParameter {
+            name: "value",
+            value,
+        });
+    }
+
+    let mut points: Vec<f64> = Vec::with_capacity(values.len() + 2);
+    points.push(lower);
+    points.extend(values.iter().map(|value| value.clamp(lower, upper)));
+    points.push(upper);
+    points.sort_unstable_by(f64::total_cmp);
+
+    // the logarithm of the weight of each interval, shifted by the maximum so that the
+    // weights don't underflow, empty intervals have a weight of 0
+    let target = quantile * values.len() as f64;
+    let log_weights: Vec<f64> = points
+        .windows(2)
+        .enumerate()
+        .map(|(rank, interval)| {
+            (interval[1] - interval[0]).ln() - epsilon * (rank as f64 - target).abs() / 2.0
+        })
+        .collect();
+    let max_log_weight = log_weights
+        .iter()
+        .copied()
+        .fold(f64::NEG_INFINITY, f64::max);
+    let weight = |log_weight: f64| (log_weight - max_log_weight).exp();
+    let total: f64 = log_weights
+        .iter()
+        .map(|&log_weight| weight(log_weight))
+        .sum();
+    let mut threshold = rng.gen::<f64>() * total;
+    let mut selected = log_weights.len() - 1;
+    for (index, &log_weight) in log_weights.iter().enumerate() {
+        threshold -= weight(log_weight);
+        if threshold < 0.0 {
+            selected = index;
+            break;
+        }
+    }
+    let (start, end) = (points[selected], points[selected + 1]);
+    Ok((start + rng.gen::<f64>() * (end - start)).clamp(lower, upper))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_exponential_mechanism_prefers_higher_scores() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let candidates = ["low", "medium", "high"];
+        let scores = [0.0, 2.0, 5.0];
+        let mut selected = [0; 3];
+        for _ in 0..10_000 {
+            let index = exponential_mechanism(&candidates, &scores, 1.0, 1.0, &mut rng).unwrap();
+            selected[index] += 1;
+        }
+        assert!(selected[0] < selected[1]);
+        assert!(selected[1] < selected[2]);
+        assert!(selected[0] > 0);
+    }
+
+    #[test]
+    fn test_exponential_mechanism_length_mismatch() {
+        let mut rng = rand::thread_rng();
+        let result = exponential_mechanism(&[1, 2], &[1.0], 1.0, 1.0, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::LengthMismatch(2, 1))));
+    }
+
+    #[test]
+    fn test_exponential_mechanism_empty() {
+        let mut rng = rand::thread_rng();
+        let result = exponential_mechanism::<u64, _>(&[], &[], 1.0, 1.0, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::EmptyInput)));
+    }
+
+    #[test]
+    fn test_exponential_mechanism_invalid_parameters() {
+        let mut rng = rand::thread_rng();
+        let result = exponential_mechanism(&[1], &[1.0], 0.0, 1.0, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::InvalidSensitivity(_))));
+        let result = exponential_mechanism(&[1], &[f64::NAN], 1.0, 1.0, &mut rng);
+        assert!(matches!(
+            result,
+            Err(LaplaceError::NonFiniteParameter { name: "score", .. })
+        ));
+    }
+
+    #[test]
+    fn test_report_noisy_max_dominant_count() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let counts = [10, 500, 20, 15];
+        let selected = (0..1000)
+            .filter(|_| report_noisy_max(&counts, 1.0, 1.0, &mut rng).unwrap() == 1)
+            .count();
+        assert!(selected >= 999);
+    }
+
+    #[test]
+    fn test_report_noisy_max_close_counts() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let counts = [100, 101];
+        let mut selected = [0; 2];
+        for _ in 0..1000 {
+            selected[report_noisy_max(&counts, 1.0, 0.5, &mut rng).unwrap()] += 1;
+        }
+        assert!(selected[0] > 100);
+        assert!(selected[1] > 100);
+    }
+
+    #[test]
+    fn test_privatize_quantile_near_true_quantile() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let values: Vec<f64> = (0..10_001).map(|_| rng.gen_range(0.0..1000.0)).collect();
+        let mut sorted = values.clone();
+        sorted.sort_unstable_by(f64::total_cmp);
+        for quantile in [0.1, 0.5, 0.9] {
+            let expected = sorted[(quantile * 10_000.0) as usize];
+            for _ in 0..20 {
+                let estimate =
+                    privatize_quantile(&values, quantile, 0.0, 1000.0, 1.0, &mut rng).unwrap();
+                assert!((estimate - expected).abs() < 10.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_privatize_quantile_within_bounds() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let values = [-50.0, 3.0, 3.0, 3.0, 500.0];
+        for quantile in [0.0, 0.5, 1.0] {
+            for _ in 0..1000 {
+                let estimate =
+                    privatize_quantile(&values, quantile, 0.0, 10.0, 0.1, &mut rng).unwrap();
+                assert!((0.0..=10.0).contains(&estimate));
+            }
+        }
+        let estimate = privatize_quantile(&[], 0.5, 0.0, 10.0, 1.0, &mut rng).unwrap();
+        assert!((0.0..=10.0).contains(&estimate));
+    }
+
+    #[test]
+    fn test_privatize_quantile_invalid() {
+        let mut rng = rand::thread_rng();
+        let values = [1.0, 2.0, 3.0];
+        for quantile in [-0.1, 1.1, f64::NAN] {
+            assert!(matches!(
+                privatize_quantile(&values, quantile, 0.0, 10.0, 1.0, &mut rng),
+                Err(LaplaceError::InvalidQuantile(_))
+            ));
+        }
+        for (lower, upper) in [(10.0, 0.0), (5.0, 5.0), (0.0, f64::INFINITY)] {
+            assert!(matches!(
+                privatize_quantile(&values, 0.5, lower, upper, 1.0, &mut rng),
+                Err(LaplaceError::InvalidOutputBounds(_, _))
+            ));
+        }
+        assert!(matches!(
+            privatize_quantile(&values, 0.5, 0.0, 10.0, 0.0, &mut rng),
+            Err(LaplaceError::InvalidEpsilon(_))
+        ));
+        assert!(matches!(
+            privatize_quantile(&[1.0, f64::NAN], 0.5, 0.0, 10.0, 1.0, &mut rng),
+            Err(LaplaceError::NonFiniteParameter { name: "value", .. })
+        ));
+    }
+
+    #[test]
+    fn test_report_noisy_max_empty() {
+        let mut rng = rand::thread_rng();
+        let result = report_noisy_max(&[], 1.0, 1.0, &mut rng);
+        assert!(matches!(result, Err(LaplaceError::EmptyInput)));
+    }
+}
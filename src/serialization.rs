@@ -0,0 +1,136 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{CacheRecord, ObfCache};
+
+/// Serializes the obfuscation cache as a list of `{sensitivity, epsilon, count, bin, value}`
+/// records, since tuple keys can't be map keys. The size limit of the cache is not serialized.
+/// The records are sorted like `ObfCache::to_manifest`, so that the same entries always
+/// serialize to the same bytes, e.g. for reviewing diffs of snapshots.
+impl Serialize for ObfCache {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.to_manifest())
+    }
+}
+
+/// Deserializes an unbounded obfuscation cache from a list of
+/// `{sensitivity, epsilon, count, bin, value}` records.
+impl<'de> Deserialize<'de> for ObfCache {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let records = Vec::<CacheRecord>::deserialize(deserializer)?;
+        let mut obf_cache = ObfCache::new();
+        for record in records {
+            let key = (
+                record.sensitivity.to_bits(),
+                record.epsilon.to_bits(),
+                record.count,
+                record.bin,
+            );
+            obf_cache.insert(key, record.value);
+        }
+        Ok(obf_cache)
+    }
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod test {
+    use super::*;
+    use crate::config::DiffPrivConfig;
+    use crate::{ObfuscateBelow10Mode, SmallCountPolicy, SuppressionMode};
+
+    #[test]
+    fn test_obf_cache_json_round_trip() {
+        let mut obf_cache = ObfCache::new();
+        obf_cache.insert((1f64.to_bits(), 1f64.to_bits(), 10, 1), 20);
+        obf_cache.insert((1f64.to_bits(), 1f64.to_bits(), 10, 2), 10);
+        obf_cache.insert((1f64.to_bits(), 0.5f64.to_bits(), 10, 2), 15);
+        obf_cache.insert((2f64.to_bits(), 1f64.to_bits(), 35, 1), 40);
+
+        let json = serde_json::to_string(&obf_cache).unwrap();
+        let deserialized: ObfCache = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.cache, obf_cache.cache);
+    }
+
+    #[test]
+    fn test_obf_cache_json_records() {
+        let mut obf_cache = ObfCache::new();
+        obf_cache.insert((1f64.to_bits(), 0.5f64.to_bits(), 10, 2), 20);
+
+        let json = serde_json::to_value(&obf_cache).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"sensitivity": 1.0, "epsilon": 0.5, "count": 10, "bin": 2, "value": 20}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_obf_cache_json_stable() {
+        let keys = [
+            (1f64.to_bits(), 1f64.to_bits(), 10, 1),
+            (1f64.to_bits(), 1f64.to_bits(), 10, 2),
+            (1f64.to_bits(), 0.5f64.to_bits(), 10, 2),
+            (2f64.to_bits(), 1f64.to_bits(), 35, 1),
+            (2f64.to_bits(), 1f64.to_bits(), 5, 3),
+        ];
+        let mut obf_cache = ObfCache::new();
+        for (value, &key) in keys.iter().enumerate() {
+            obf_cache.insert(key, value as u64);
+        }
+        let mut reversed = ObfCache::new();
+        for (value, &key) in keys.iter().enumerate().rev() {
+            reversed.insert(key, value as u64);
+        }
+
+        let json = serde_json::to_vec(&obf_cache).unwrap();
+        assert_eq!(json, serde_json::to_vec(&reversed).unwrap());
+        let deserialized: ObfCache = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json, serde_json::to_vec(&deserialized).unwrap());
+    }
+
+    #[test]
+    fn test_below_10_mode_json_names() {
+        let json = serde_json::to_string(&ObfuscateBelow10Mode::Obfuscate).unwrap();
+        assert_eq!(json, "\"obfuscate\"");
+        let mode: ObfuscateBelow10Mode = serde_json::from_str("\"ten\"").unwrap();
+        assert!(mode == ObfuscateBelow10Mode::Ten);
+        assert!(serde_json::from_str::<ObfuscateBelow10Mode>("1").is_err());
+    }
+
+    #[test]
+    fn test_diff_priv_config_json_round_trip() {
+        let config = DiffPrivConfig::new()
+            .sensitivity(2.0)
+            .epsilon(0.5)
+            .rounding_step(10)
+            .obfuscate_zero(false)
+            .small_count_policy(SmallCountPolicy {
+                threshold: 5,
+                mode: SuppressionMode::Clamp(5),
+            })
+            .domain_max(Some(1000));
+
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["small_count_policy"]["mode"]["clamp"], 5);
+        let deserialized: DiffPrivConfig = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&deserialized).unwrap(), json);
+    }
+
+    #[test]
+    fn test_diff_priv_config_json_delta_alias() {
+        let config: DiffPrivConfig = serde_json::from_str(r#"{"delta": 2.0}"#).unwrap();
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["sensitivity"], 2.0);
+    }
+
+    #[test]
+    fn test_diff_priv_config_json_defaults() {
+        let config: DiffPrivConfig = serde_json::from_str(r#"{"epsilon": 0.1}"#).unwrap();
+        let expected = DiffPrivConfig::new().epsilon(0.1);
+        assert_eq!(
+            serde_json::to_value(&config).unwrap(),
+            serde_json::to_value(&expected).unwrap()
+        );
+    }
+}
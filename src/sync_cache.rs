@@ -0,0 +1,215 @@
+use rand::Rng;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{PoisonError, RwLock};
+
+use crate::config::DiffPrivConfig;
+use crate::errors::LaplaceError;
+#[allow(deprecated)]
+use crate::{Bin, CacheKey, ObfuscateBelow10Mode};
+
+/// An obfuscation cache that can be shared between threads, e.g. the tasks of a web server,
+/// without wrapping it in a `Mutex`. Lookups of cached values only take a read lock.
+#[derive(Default)]
+pub struct SyncObfCache {
+    cache: RwLock<HashMap<CacheKey, u64>>,
+}
+
+/// Summarizes the size of the cache instead of listing every entry.
+impl fmt::Debug for SyncObfCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SyncObfCache")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl SyncObfCache {
+    /// Creates an empty shared obfuscation cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of cached obfuscated values.
+    pub fn len(&self) -> usize {
+        self.cache
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Returns true if the cache holds no obfuscated values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached obfuscated values.
+    pub fn clear(&self) {
+        self.cache
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    // Returns the cached value, or computes and caches it. If several threads compute a value
+    // for the same key concurrently, the first inserted value wins and is returned to all.
+    pub(crate) fn get_or_try_insert_with(
+        &self,
+        key: CacheKey,
+        compute: impl FnOnce() -> Result<u64, LaplaceError>,
+    ) -> Result<u64, LaplaceError> {
+        let cached = self
+            .cache
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&key)
+            .copied();
+        if let Some(value) = cached {
+            return Ok(value);
+        }
+        let value = compute()?;
+        Ok(*self
+            .cache
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(key)
+            .or_insert(value))
+    }
+}
+
+/// Obfuscates the given value like `get_from_cache_or_privatize`, using a cache that can be
+/// shared between threads.
+///
+/// # Arguments
+///
+/// * value - The input value to be obfuscated.
+/// * sensitivity - Sensitivity of query.
+/// * epsilon - Privacy budget parameter.
+/// * bin - The bin that the value belongs to.
+/// * obf_cache - The shared obfuscation cache.
+/// * obfuscate_zero - A flag indicating whether zero counts should be obfuscated.
+/// * below_10_obfuscation_mode: 0 - return 0, 1 - return 10, 2 - obfuscate using Laplace distribution and rounding
+/// * rounding_step - The granularity of the rounding.
+/// * rng - A secure random generator for seeded randomness.
+///
+/// # Returns
+///
+/// The obfuscated value, rounded to the nearest multiple of the rounding_step, or an error if the
+/// obfuscation failed.
+#[allow(clippy::too_many_arguments, deprecated)]
+pub fn get_from_sync_cache_or_privatize<R: Rng + ?Sized>(
+    value: u64,
+    sensitivity: f64,
+    epsilon: f64,
+    bin: Bin,
+    obf_cache: &SyncObfCache,
+    obfuscate_zero: bool,
+    obfuscate_below_10_mode: ObfuscateBelow10Mode,
+    rounding_step: usize,
+    rng: &mut R,
+) -> Result<u64, LaplaceError> {
+    DiffPrivConfig::new()
+        .sensitivity(sensitivity)
+        .epsilon(epsilon)
+        .rounding_step(rounding_step)
+        .obfuscate_zero(obfuscate_zero)
+        .small_count_policy(obfuscate_below_10_mode.into())
+        .privatize_sync(value, bin, obf_cache, rng)
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod test {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_sync_obf_cache_reuses_values() {
+        let mut rng = rand::thread_rng();
+        let obf_cache = SyncObfCache::new();
+        let first = get_from_sync_cache_or_privatize(
+            10,
+            1.0,
+            0.1,
+            1,
+            &obf_cache,
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        )
+        .unwrap();
+        let second = get_from_sync_cache_or_privatize(
+            10,
+            1.0,
+            0.1,
+            1,
+            &obf_cache,
+            true,
+            ObfuscateBelow10Mode::Obfuscate,
+            1,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(obf_cache.len(), 1);
+
+        obf_cache.clear();
+        assert!(obf_cache.is_empty());
+    }
+
+    #[test]
+    fn test_sync_obf_cache_suppression() {
+        let mut rng = rand::thread_rng();
+        let obf_cache = SyncObfCache::new();
+        let result = get_from_sync_cache_or_privatize(
+            5,
+            1.0,
+            0.1,
+            1,
+            &obf_cache,
+            true,
+            ObfuscateBelow10Mode::Ten,
+            1,
+            &mut rng,
+        );
+        assert_eq!(result.unwrap(), 10);
+        assert!(obf_cache.is_empty());
+    }
+
+    #[test]
+    fn test_sync_obf_cache_concurrent() {
+        let obf_cache = Arc::new(SyncObfCache::new());
+        let handles: Vec<_> = (0..16)
+            .map(|_| {
+                let obf_cache = Arc::clone(&obf_cache);
+                thread::spawn(move || {
+                    let mut rng = rand::thread_rng();
+                    (10..60)
+                        .map(|value| {
+                            get_from_sync_cache_or_privatize(
+                                value,
+                                1.0,
+                                0.1,
+                                1,
+                                &obf_cache,
+                                true,
+                                ObfuscateBelow10Mode::Obfuscate,
+                                1,
+                                &mut rng,
+                            )
+                            .unwrap()
+                        })
+                        .collect::<Vec<u64>>()
+                })
+            })
+            .collect();
+        let results: Vec<Vec<u64>> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        assert_eq!(obf_cache.len(), 50);
+        for result in &results {
+            assert_eq!(result, &results[0]);
+        }
+    }
+}